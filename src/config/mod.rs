@@ -0,0 +1,415 @@
+//  how worlds get generated. Every setting can be given in a config file of
+//  `key = value` lines, or as a `--key value` flag, which wins over the file:
+//
+//      # the same house every time
+//      seed         = 1234
+//      rooms        = 5-10
+//      link-density = 2.0
+//      one-way      = 0.1
+//      twisted      = 0.1
+//      locks        = 2
+//      room-width   = 10-14
+//      room-height  = 10-14
+//      room-types   = square:1
+//      glitch       = 2
+//      camera       = room
+//      tick-rate    = 30
+//
+//  ranges are inclusive, `link-density` is links per room, up to 10, `one-way` and
+//  `twisted` are the shares of links that don't lead back the way they came,
+//  `locks` is how many doors need a key, at most, `room-types` is a
+//  comma-separated list of kinds with how likely each one is, and `camera` is
+//  one of follow, room, deadzone and look-ahead, and `tick-rate` is how many
+//  times per second the world updates
+
+use game::endless::{ ROOM_KINDS, Item, CameraMode };
+
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+
+/// How glitchy the screen gets unless told otherwise.
+pub const DEFAULT_GLITCH: u8 = 2;
+
+/// The glitchiest it gets.
+pub const MAX_GLITCH: u8 = 4;
+
+/// How many times per second the world updates unless told otherwise.
+pub const DEFAULT_TICK_RATE: u32 = 30;
+
+/// The most ticks per second; past this nobody can tell the difference.
+pub const MAX_TICK_RATE: u32 = 120;
+
+/// The most links per room; every link is another door, and more than this
+/// makes rooms that are all doors.
+const MAX_LINK_DENSITY: f32 = 10.0;
+
+/// The setting keys, which are also the flag names.
+pub const KEYS: [&str; 12] = [
+    "seed", "rooms", "link-density", "one-way", "twisted", "locks", "room-width",
+    "room-height", "room-types", "glitch", "camera", "tick-rate",
+];
+
+/// What went wrong putting a config together.
+#[derive(Debug)]
+pub enum ConfigError {
+    Io(io::Error),
+    Parse(usize, String),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &ConfigError::Io(ref e) => write!(f, "could not read config: {}", e),
+            &ConfigError::Parse(line, ref msg) =>
+                write!(f, "config line {}: {}", line, msg),
+            &ConfigError::Invalid(ref msg) => write!(f, "{}", msg),
+        }
+    }
+}
+
+impl From<io::Error> for ConfigError {
+    fn from(e: io::Error) -> Self {
+        ConfigError::Io(e)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// Seed for everything random in the game, or None for a new one each
+    /// game.
+    pub seed: Option<u64>,
+
+    /// Inclusive ranges.
+    pub rooms: (usize, usize),
+    pub room_width: (usize, usize),
+    pub room_height: (usize, usize),
+
+    /// Links per room, on average.
+    pub link_density: f32,
+
+    /// Shares of links that have no way back, and that lead back elsewhere.
+    pub one_way: f32,
+    pub twisted: f32,
+
+    /// How many doors get locked, if there are enough of them.
+    pub locks: usize,
+
+    /// Room kinds and how likely each is.
+    pub room_types: Vec<(String, u32)>,
+
+    pub glitch: u8,
+
+    /// How the map follows the player, to begin with.
+    pub camera: CameraMode,
+
+    /// Ticks per second.
+    pub tick_rate: u32,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            seed: None,
+            rooms: (5, 10),
+            room_width: (10, 14),
+            room_height: (10, 14),
+            link_density: 2.0,
+            one_way: 0.1,
+            twisted: 0.1,
+            locks: 2,
+            room_types: vec![(String::from("square"), 1)],
+            glitch: DEFAULT_GLITCH,
+            camera: CameraMode::Room,
+            tick_rate: DEFAULT_TICK_RATE,
+        }
+    }
+}
+
+impl Config {
+    /// Returns the config given by the command line: the file named by
+    /// `--config`, if any, with any setting flags on top. Checks that the
+    /// result makes sense.
+    pub fn from_args(args: &[String]) -> Result<Self, ConfigError> {
+        let mut res = match args.iter().position(|arg| arg == "--config") {
+            None => Config::default(),
+            Some(i) => match args.get(i + 1) {
+                Some(path) => Config::load(path)?,
+                None => {
+                    return Err(ConfigError::Invalid(String::from("--config needs a file")));
+                },
+            },
+        };
+
+        for key in KEYS.iter() {
+            let flag = format!("--{}", key);
+            let i = match args.iter().position(|arg| *arg == flag) {
+                Some(i) => i,
+                None => continue,
+            };
+
+            let value = args.get(i + 1).ok_or_else(||
+                ConfigError::Invalid(format!("{} needs a value", flag))
+            )?;
+
+            res.set(key, value)
+                .map_err(|msg| ConfigError::Invalid(format!("{}: {}", flag, msg)))?;
+        }
+
+        res.validate()?;
+        Ok(res)
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ConfigError> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+
+        Config::parse(&text)
+    }
+
+    /// Returns the default config changed by given config file contents.
+    pub fn parse(text: &str) -> Result<Self, ConfigError> {
+        let mut res = Config::default();
+
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut parts = line.splitn(2, '=');
+            let key = parts.next().unwrap_or("").trim();
+            let value = match parts.next() {
+                Some(value) => value.trim(),
+                None => {
+                    return Err(ConfigError::Parse(i + 1, String::from("expected `key = value`")));
+                },
+            };
+
+            res.set(key, value).map_err(|msg| ConfigError::Parse(i + 1, msg))?;
+        }
+
+        Ok(res)
+    }
+
+    /// Changes a single setting.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        match key {
+            "seed" => { self.seed = Some(parse_num(value)?); },
+            "rooms" => { self.rooms = parse_range(value)?; },
+            "room-width" => { self.room_width = parse_range(value)?; },
+            "room-height" => { self.room_height = parse_range(value)?; },
+            "link-density" => { self.link_density = parse_num(value)?; },
+            "one-way" => { self.one_way = parse_num(value)?; },
+            "twisted" => { self.twisted = parse_num(value)?; },
+            "locks" => { self.locks = parse_num(value)?; },
+            "glitch" => { self.glitch = parse_num(value)?; },
+            "tick-rate" => { self.tick_rate = parse_num(value)?; },
+
+            "camera" => {
+                self.camera = CameraMode::from_key(value).ok_or_else(|| {
+                    let keys: Vec<&str> = CameraMode::ALL.iter().map(|mode| mode.key()).collect();
+                    format!("unknown camera `{}`, expected one of: {}", value, keys.join(", "))
+                })?;
+            },
+
+            "room-types" => {
+                self.room_types = value.split(',')
+                    .map(|kind| {
+                        let mut parts = kind.splitn(2, ':');
+                        let name = parts.next().unwrap_or("").trim();
+                        let weight = match parts.next() {
+                            Some(weight) => parse_num(weight)?,
+                            None => 1,
+                        };
+
+                        Ok((String::from(name), weight))
+                    })
+                    .collect::<Result<_, String>>()?;
+            },
+
+            _ => {
+                return Err(format!("unknown setting `{}`, expected one of: {}", key, KEYS.join(", ")));
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the settings make sense together.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |msg: String| Err(ConfigError::Invalid(msg));
+
+        for &(name, (min, max), least) in [
+            ("rooms", self.rooms, 1),
+            ("room-width", self.room_width, 3),
+            ("room-height", self.room_height, 3),
+        ].iter() {
+            if min > max {
+                return invalid(format!("{} goes from {} down to {}", name, min, max));
+            }
+            if min < least {
+                return invalid(format!("{} must be at least {}", name, least));
+            }
+        }
+
+        if !(self.link_density.is_finite() && self.link_density >= 0.0
+            && self.link_density <= MAX_LINK_DENSITY) {
+            return invalid(format!("link-density goes from 0 to {}", MAX_LINK_DENSITY));
+        }
+
+        for &(name, share) in [("one-way", self.one_way), ("twisted", self.twisted)].iter() {
+            if !(share >= 0.0 && share <= 1.0) {
+                return invalid(format!("{} must be between 0 and 1", name));
+            }
+        }
+
+        if self.one_way + self.twisted > 1.0 {
+            return invalid(String::from("one-way and twisted add up to more than all links"));
+        }
+
+        if self.locks > Item::MAX_KEYS {
+            return invalid(format!("there are only {} kinds of keys to go with locks", Item::MAX_KEYS));
+        }
+
+        if self.rooms.1 == 1 && self.link_density > 0.0 {
+            return invalid(String::from("a single room has nothing to link to, use link-density 0"));
+        }
+
+        if self.room_types.iter().all(|&(_, weight)| weight == 0) {
+            return invalid(String::from("room-types needs at least one kind that can turn up"));
+        }
+
+        if let Some(&(ref name, _)) = self.room_types.iter().find(|&&(ref name, _)| !ROOM_KINDS.contains(&name.as_str())) {
+            return invalid(format!("unknown room type `{}`, expected one of: {}", name, ROOM_KINDS.join(", ")));
+        }
+
+        if self.glitch > MAX_GLITCH {
+            return invalid(format!("glitch goes from 0 to {}", MAX_GLITCH));
+        }
+
+        if self.tick_rate < 1 || self.tick_rate > MAX_TICK_RATE {
+            return invalid(format!("tick-rate goes from 1 to {}", MAX_TICK_RATE));
+        }
+
+        Ok(())
+    }
+}
+
+fn parse_num<T: ::std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.trim().parse::<T>().map_err(|_| format!("`{}` is not a valid number", value.trim()))
+}
+
+//  `5-10`, or just `7` for exactly that
+fn parse_range(value: &str) -> Result<(usize, usize), String> {
+    let mut parts = value.splitn(2, '-');
+    let min = parse_num(parts.next().unwrap_or(""))?;
+    let max = match parts.next() {
+        Some(max) => parse_num(max)?,
+        None => min,
+    };
+
+    Ok((min, max))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(line: &str) -> Vec<String> {
+        line.split_whitespace().map(String::from).collect()
+    }
+
+    fn invalid(text: &str) -> String {
+        match Config::parse(text).and_then(|config| config.validate().map(|_| config)) {
+            Err(ConfigError::Invalid(msg)) => msg,
+            Err(e) => panic!("expected the config to be invalid, got {}", e),
+            Ok(_) => panic!("expected the config to be invalid"),
+        }
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        assert!(Config::default().validate().is_ok());
+    }
+
+    #[test]
+    fn file() {
+        let config = Config::parse(concat!(
+            "# the same house every time\n",
+            "seed = 1234\n",
+            "\n",
+            "rooms        = 3-4\n",
+            "room-width   = 9\n",
+            "link-density = 1.5\n",
+            "room-types   = square:2, square\n",
+            "camera       = deadzone\n",
+            "tick-rate    = 60\n",
+        )).unwrap();
+
+        assert_eq!(config.seed, Some(1234));
+        assert_eq!(config.rooms, (3, 4));
+        assert_eq!(config.room_width, (9, 9));
+        assert_eq!(config.link_density, 1.5);
+        assert_eq!(config.room_types, vec![(String::from("square"), 2), (String::from("square"), 1)]);
+        assert_eq!(config.camera, CameraMode::Deadzone);
+        assert_eq!(config.tick_rate, 60);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn parse_errors_say_where() {
+        for &(text, line) in [
+            ("rooms = 5-\n", 1),
+            ("\nseed\n", 2),
+            ("seed = 1\ncolour = red\n", 2),
+            ("camera = sideways\n", 1),
+            ("room-types = square:lots\n", 1),
+        ].iter() {
+            match Config::parse(text) {
+                Err(ConfigError::Parse(at, _)) => assert_eq!(at, line, "{:?}", text),
+                _ => panic!("expected a parse error for {:?}", text),
+            }
+        }
+    }
+
+    #[test]
+    fn out_of_range() {
+        assert!(invalid("rooms = 10-5").contains("rooms"));
+        assert!(invalid("room-width = 2").contains("room-width"));
+        assert!(invalid("link-density = -1").contains("link-density"));
+        assert!(invalid("link-density = inf").contains("link-density"));
+        assert!(invalid("link-density = NaN").contains("link-density"));
+        assert!(invalid("link-density = 11").contains("link-density"));
+        assert!(invalid("one-way = 1.5").contains("one-way"));
+        assert!(invalid("one-way = 0.6\ntwisted = 0.6").contains("add up"));
+        assert!(invalid("locks = 7").contains("keys"));
+        assert!(invalid("rooms = 1").contains("single room"));
+        assert!(invalid("room-types = square:0").contains("room-types"));
+        assert!(invalid("room-types = attic").contains("attic"));
+        assert!(invalid("glitch = 5").contains("glitch"));
+        assert!(invalid("tick-rate = 0").contains("tick-rate"));
+        assert!(invalid("tick-rate = 121").contains("tick-rate"));
+    }
+
+    #[test]
+    fn flags_win_over_defaults() {
+        let config = Config::from_args(&args("oaendlig --seed 9 --rooms 2-3 --camera follow")).unwrap();
+        assert_eq!(config.seed, Some(9));
+        assert_eq!(config.rooms, (2, 3));
+        assert_eq!(config.camera, CameraMode::Follow);
+
+        match Config::from_args(&args("oaendlig --rooms")) {
+            Err(ConfigError::Invalid(msg)) => assert!(msg.contains("--rooms")),
+            _ => panic!("expected a missing value to be invalid"),
+        }
+
+        match Config::from_args(&args("oaendlig --tick-rate fast")) {
+            Err(ConfigError::Invalid(msg)) => assert!(msg.contains("--tick-rate")),
+            _ => panic!("expected a bad number to be invalid"),
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//  a chtype can only hold an 8-bit character, so the console keeps its own
+//  cells and only falls back to chtypes when talking to curses
+
+use pancurses::*;
+
+//  pancurses' A_CHARTEXT is wrong on some platforms
+const CHARTEXT: chtype = 0xff;
+
+/// A single character on screen, with its curses attributes.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Cell {
+    pub ch: char,
+    pub attr: chtype,
+}
+
+impl Cell {
+    pub fn new(ch: char, attr: chtype) -> Self {
+        Self {
+            ch: ch,
+            attr: attr,
+        }
+    }
+
+    /// Returns a cell that changes nothing when or'ed onto another.
+    pub fn empty() -> Self {
+        Self {
+            ch: '\0',
+            attr: A_NORMAL,
+        }
+    }
+
+    /// Splits a chtype, e.g. one of the `ACS_*` characters, into a cell.
+    pub fn from_chtype(ch: chtype) -> Self {
+        Self {
+            ch: (ch & CHARTEXT) as u8 as char,
+            attr: ch & !CHARTEXT,
+        }
+    }
+
+    /// Returns whether curses can draw this cell as a single chtype.
+    pub fn is_narrow(&self) -> bool {
+        self.ch.is_ascii() || self.attr & A_ALTCHARSET != 0
+    }
+
+    /// Returns this cell as a chtype. Only meaningful if `is_narrow()`.
+    pub fn to_chtype(&self) -> chtype {
+        (self.ch as u8 as chtype) | self.attr
+    }
+
+    /// Combines two cells the way or'ing two chtypes would. Characters
+    /// outside of ASCII can't be or'ed, so the other one wins.
+    pub fn or(&self, other: Cell) -> Cell {
+        let ch = match (self.ch, other.ch) {
+            (a, '\0') => a,
+            ('\0', b) => b,
+            (a, b) if a.is_ascii() && b.is_ascii() =>
+                ((a as u8) | (b as u8)) as char,
+            (_, b) => b,
+        };
+
+        Cell {
+            ch: ch,
+            attr: self.attr | other.attr,
+        }
+    }
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Cell::new(' ', A_NORMAL)
+    }
+}
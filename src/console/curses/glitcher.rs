@@ -1,6 +1,8 @@
 //  stupid Self not matching enum issue addressed in #26264, #31168
 
-use utils::{ rnd_lt, rnd_ch, clamp };
+use console::curses::cell::Cell;
+use config::DEFAULT_GLITCH;
+use utils::{ fx_lt, fx_ch, clamp };
 
 use pancurses::*;
 
@@ -13,20 +15,20 @@ enum GlitchPosition {
 
 impl GlitchPosition {
     fn rnd() -> Self {
-        match rnd_lt::<u8>(4) {
+        match fx_lt::<u8>(4) {
             0 => GlitchPosition::Keep,
-            1 => GlitchPosition::ShiftX(rnd_lt::<isize>(10) - 5),
-            2 => GlitchPosition::ShiftY(rnd_lt::<isize>(10) - 5),
+            1 => GlitchPosition::ShiftX(fx_lt::<isize>(10) - 5),
+            2 => GlitchPosition::ShiftY(fx_lt::<isize>(10) - 5),
             _ => GlitchPosition::ShiftXY(
-                rnd_lt::<isize>(10) - 5,
-                rnd_lt::<isize>(10) - 5
+                fx_lt::<isize>(10) - 5,
+                fx_lt::<isize>(10) - 5
                 ),
         }
     }
 
-    fn get_ch(&self, x: usize, y: usize, w: usize, h: usize, buffer: &Vec<chtype>) -> chtype {
+    fn get_ch(&self, x: usize, y: usize, w: usize, h: usize, buffer: &Vec<Cell>) -> Cell {
         match self {
-            GlitchPosition::Keep => A_NORMAL,
+            GlitchPosition::Keep => Cell::empty(),
             GlitchPosition::ShiftX(sx) => {
                 let ax = clamp(x as isize + sx + (y * 5 / h) as isize, 0, w as isize - 1) as usize;
                 buffer[y * w + ax]
@@ -48,31 +50,31 @@ impl GlitchPosition {
 
 enum GlitchChar {
     Keep,
-    Char(chtype),
+    Char(char),
     Shift(usize),
     Endless,
 }
 
 impl GlitchChar {
     fn rnd() -> Self {
-        match rnd_lt::<u8>(4) {
+        match fx_lt::<u8>(4) {
             0 => GlitchChar::Keep,
-            1 => GlitchChar::Char(rnd_ch() as chtype),
-            2 => GlitchChar::Shift(rnd_lt::<usize>(10)),
+            1 => GlitchChar::Char(fx_ch()),
+            2 => GlitchChar::Shift(fx_lt::<usize>(10)),
             _ => GlitchChar::Endless,
         }
     }
 
-    fn get_ch(&self, x: usize, y: usize, ch: chtype) -> chtype {
+    fn get_ch(&self, x: usize, y: usize, ch: Cell) -> Cell {
         match self {
-            GlitchChar::Keep => A_NORMAL,
-            GlitchChar::Char(the_ch) => *the_ch,
-            GlitchChar::Shift(x) => match char::is_ascii_graphic(&((ch + *x as chtype) as u8 as char)) {
-                true => ch + *x as chtype,
+            GlitchChar::Keep => Cell::empty(),
+            GlitchChar::Char(the_ch) => Cell::new(*the_ch, A_NORMAL),
+            GlitchChar::Shift(x) => match ch.ch.is_ascii() && char::is_ascii_graphic(&((ch.ch as u8 as usize + *x) as u8 as char)) {
+                true => Cell::new((ch.ch as u8 as usize + *x) as u8 as char, ch.attr),
                 false => ch,
             },
 
-            GlitchChar::Endless => (match (x + y) % 8 {
+            GlitchChar::Endless => Cell::new(match (x + y) % 8 {
                 0 => 'o',
                 1 => 'ä',
                 2 => 'n',
@@ -81,7 +83,7 @@ impl GlitchChar {
                 5 => 'i',
                 6 => 'g',
                 _ => ' ',
-            }) as chtype,
+            }, A_NORMAL),
         }
     }
 }
@@ -93,16 +95,16 @@ enum GlitchStyle {
 
 impl GlitchStyle {
     fn rnd() -> Self {
-        match rnd_lt::<u8>(2) {
+        match fx_lt::<u8>(2) {
             0 => GlitchStyle::Keep,
-            _ => GlitchStyle::Style(match rnd_lt::<u8>(6) {
+            _ => GlitchStyle::Style(match fx_lt::<u8>(6) {
                 0 => A_ITALIC,
                 1 => A_REVERSE,
                 2 => A_BOLD,
                 3 => A_UNDERLINE,
                 4 => A_STRIKEOUT,
                 _ => A_LEFTLINE,
-            } | COLOR_PAIR(match rnd_lt::<u8>(4) {
+            } | COLOR_PAIR(match fx_lt::<u8>(4) {
                 0 => 0,
                 1 => 1,
                 2 => 6,
@@ -134,13 +136,13 @@ impl GlitchType {
         }
     }
 
-    fn get_ch(&self, ch: chtype, x: usize, y: usize, w: usize, h: usize, buffer: &Vec<chtype>) -> chtype {
+    fn get_ch(&self, ch: Cell, x: usize, y: usize, w: usize, h: usize, buffer: &Vec<Cell>) -> Cell {
         let ch = match self.pos {
             GlitchPosition::Keep => self.ch.get_ch(x, y, ch),
             _ => self.pos.get_ch(x, y, w, h, buffer),
         };
 
-        ch | self.style.get_ch()
+        ch.or(Cell::new('\0', self.style.get_ch()))
     }
 }
 
@@ -154,14 +156,14 @@ struct GlitchRect {
 
 impl GlitchRect {
     fn new(ww: usize, wh: usize) -> Self {
-        let x = rnd_lt::<usize>(ww);
-        let y = rnd_lt::<usize>(wh);
+        let x = fx_lt::<usize>(ww);
+        let y = fx_lt::<usize>(wh);
 
         Self {
             x: x,
             y: y,
-            w: rnd_lt(ww - x),
-            h: rnd_lt(wh - y),
+            w: fx_lt(ww - x),
+            h: fx_lt(wh - y),
             ty: GlitchType::rnd(),
         }
     }
@@ -170,39 +172,87 @@ impl GlitchRect {
         x >= self.x && x < self.x + self.w && y >= self.y && y < self.y + self.h
     }
 
-    fn get_ch(&self, ch: chtype, x: usize, y: usize, w: usize, h: usize, buffer: &Vec<chtype>) -> chtype {
+    fn get_ch(&self, ch: Cell, x: usize, y: usize, w: usize, h: usize, buffer: &Vec<Cell>) -> Cell {
         self.ty.get_ch(ch, x, y, w, h, buffer)
     }
 }
 
+//  rendering is split in two: `compose` works out what every changed cell
+//  should look like, and `present` sends only the cells that differ from what
+//  the terminal already shows
 pub struct Glitcher {
-    buffer: Vec<chtype>,
+    buffer: Vec<Cell>,
     rects: Vec<GlitchRect>,
     width: usize,
     height: usize,
+
+    //  indices of buffer cells that changed since the last compose
+    dirty: Vec<usize>,
+
+    //  areas that were glitched when we last composed, as (x, y, w, h)
+    glitched: Vec<(usize, usize, usize, usize)>,
+
+    //  the last composed frame, and what the terminal shows right now; None
+    //  means we don't know
+    composed: Vec<Cell>,
+    presented: Vec<Option<Cell>>,
+
+    //  indices of composed cells that may differ from presented ones
+    changes: Vec<usize>,
+
+    //  how glitchy things get, from 0 for not at all up to `MAX_GLITCH`
+    level: u8,
 }
 
 impl Glitcher {
     pub fn new(width: usize, height: usize) -> Self {
         Self {
-            buffer: vec![0; width * height],
+            buffer: vec![Cell::default(); width * height],
             rects: Vec::new(),
             width: width,
             height: height,
+            dirty: (0..width * height).collect(),
+            glitched: Vec::new(),
+            composed: vec![Cell::default(); width * height],
+            presented: vec![None; width * height],
+            changes: Vec::new(),
+            level: DEFAULT_GLITCH,
+        }
+    }
+
+    pub fn set_level(&mut self, level: u8) {
+        self.level = level;
+        if level == 0 {
+            self.rects.clear();
         }
     }
 
     pub fn resize(&mut self, ww: usize, wh: usize) {
         self.width = ww;
         self.height = wh;
-        self.buffer.resize(ww * wh, 0);
+        self.buffer.resize(ww * wh, Cell::default());
+
+        //  the terminal was probably garbled by the resize, so forget what it
+        //  shows and redraw everything
+        self.composed = vec![Cell::default(); ww * wh];
+        self.presented = vec![None; ww * wh];
+        self.dirty = (0..ww * wh).collect();
+        self.glitched.clear();
+        self.changes.clear();
+        self.rects.retain(|r| r.x + r.w <= ww && r.y + r.h <= wh);
     }
 
-    pub fn write(&mut self, x: usize, y: usize, ch: chtype) {
+    /// Writes a cell, which may come out wrong. Returns false if it did, so
+    /// that it can be written again next frame.
+    pub fn write(&mut self, x: usize, y: usize, ch: Cell) -> bool {
         let index = y * self.width + x;
-        if index < self.buffer.len() {
-            self.buffer[index] = Self::glitch_ch(ch, 1000);
+        if index >= self.buffer.len() {
+            return true;
         }
+
+        let glitched = self.glitch_ch(ch);
+        self.set(index, glitched);
+        glitched == ch
     }
 
     pub fn write_str(&mut self, x: usize, y: usize, s: &str) {
@@ -212,18 +262,21 @@ impl Glitcher {
             let index = y * self.width + x;
             if index < self.buffer.len() {
                 for (i, ch) in s.chars().enumerate() {
-                    self.buffer[index + i] = Self::glitch_ch(
-                        ch as chtype, 1000
-                    );
+                    let ch = self.glitch_ch(Cell::new(ch, A_NORMAL));
+                    self.set(index + i, ch);
                 }
             }
         }
     }
 
     pub fn update(&mut self) {
-        match rnd_lt::<usize>(5) {
-            0...1 => {
-                if self.rects.len() < 10 {
+        if self.level == 0 {
+            return;
+        }
+
+        match fx_lt::<usize>(5) {
+            0..=1 => {
+                if self.rects.len() < self.level as usize * 5 {
                     self.rects.push(GlitchRect::new(self.width, self.height));
                 }
             },
@@ -231,17 +284,17 @@ impl Glitcher {
             2 => {
                 if !self.rects.is_empty() {
                     let len = self.rects.len();
-                    self.rects.remove(rnd_lt(len));
+                    self.rects.remove(fx_lt(len));
                 }
             },
 
-            3...4 => {
+            3..=4 => {
                 if self.rects.is_empty() {
                     return;
                 }
 
-                let index = rnd_lt(self.rects.len());
-                match rnd_lt::<u8>(4) {
+                let index = fx_lt(self.rects.len());
+                match fx_lt::<u8>(4) {
                     0 => {
                         if
                             self.rects[index].w > 1 &&
@@ -284,41 +337,96 @@ impl Glitcher {
         }
     }
 
-    pub fn render(&self, window: &Window) {
-        let mut x: usize = 0;
-        let mut y: usize = 0;
-
-        window.clear();
-        window.mv(0, 0);
-        for ch in self.buffer.iter() {
-            window.addch(self.render_ch(*ch, x, y));
-            x += 1;
-            x %= self.width;
-            if (x == 0) {
-                y += 1;
+    /// Works out the new look of every cell that was written to or is
+    /// affected by a glitch.
+    pub fn compose(&mut self) {
+        let mut indices = Vec::new();
+        indices.append(&mut self.dirty);
+
+        let areas: Vec<_> = self.rects.iter()
+            .map(|r| (r.x, r.y, r.w, r.h))
+            .collect();
+
+        for &(rx, ry, rw, rh) in self.glitched.iter().chain(areas.iter()) {
+            for y in ry..ry + rh {
+                for x in rx..rx + rw {
+                    indices.push(y * self.width + x);
+                }
+            }
+        }
+
+        self.glitched = areas;
+
+        for index in indices.into_iter() {
+            if index >= self.buffer.len() {
+                continue;
+            }
+
+            let x = index % self.width;
+            let y = index / self.width;
+            let ch = self.render_ch(self.buffer[index], x, y);
+
+            self.composed[index] = ch;
+            if self.presented[index] != Some(ch) {
+                self.changes.push(index);
+            }
+        }
+    }
+
+    /// Sends the cells that changed since the last present to the terminal.
+    pub fn present(&mut self, window: &Window) {
+        for index in self.changes.drain(..) {
+            let ch = self.composed[index];
+            if self.presented[index] == Some(ch) {
+                continue;
             }
+
+            let x = (index % self.width) as i32;
+            let y = (index / self.width) as i32;
+
+            match ch.is_narrow() {
+                true => { window.mvaddch(y, x, ch.to_chtype()); },
+                false => {
+                    window.attron(ch.attr);
+                    window.mvaddstr(y, x, ch.ch.to_string());
+                    window.attroff(ch.attr);
+                },
+            }
+
+            self.presented[index] = Some(ch);
+        }
+
+        window.refresh();
+    }
+
+    fn set(&mut self, index: usize, ch: Cell) {
+        if self.buffer[index] != ch {
+            self.buffer[index] = ch;
+            self.dirty.push(index);
         }
     }
 
-    fn render_ch(&self, ch: chtype, x: usize, y: usize) -> chtype {
+    fn render_ch(&self, ch: Cell, x: usize, y: usize) -> Cell {
         for rect in self.rects.iter() {
             if rect.contains(x, y) {
-                return ch | rect.get_ch(ch, x, y, self.width, self.height, &self.buffer);
+                return ch.or(rect.get_ch(ch, x, y, self.width, self.height, &self.buffer));
             }
         }
 
         ch
     }
 
-    fn glitch_ch(ch: chtype, chance: usize) -> chtype {
-        match chance > 0 && rnd_lt::<usize>(chance) == 0 {
+    //  every so often a written character comes out wrong, more often the
+    //  higher the level
+    fn glitch_ch(&self, ch: Cell) -> Cell {
+        match self.level > 0 && fx_lt::<usize>(2000 / self.level as usize) == 0 {
             true => Self::rnd_ch(),
             false => ch,
         }
     }
 
-    fn rnd_ch() -> chtype {
-        match rnd_lt::<u8>(17) {
+    fn rnd_ch() -> Cell {
+        Cell::from_chtype(match fx_lt::<u8>(17) {
             0 => ACS_ULCORNER(),
             1 => ACS_URCORNER(),
             2 => ACS_LLCORNER(),
@@ -337,6 +445,6 @@ impl Glitcher {
             15 => ACS_DEGREE(),
             16 => ACS_BULLET(),
             _ => ' ' as chtype,
-        }
+        })
     }
 }
@@ -1,15 +1,30 @@
 pub struct BottomMessage {
     text: String,
+
+    //  whether the player is typing into it
+    prompt: bool,
 }
 
 impl BottomMessage {
     pub fn new(text: String) -> Self {
         Self {
             text: text,
+            prompt: false,
+        }
+    }
+
+    pub fn prompt(text: String) -> Self {
+        Self {
+            text: text,
+            prompt: true,
         }
     }
 
     pub fn get_text(&self) -> &str {
         &self.text.as_str()
     }
+
+    pub fn is_prompt(&self) -> bool {
+        self.prompt
+    }
 }
@@ -1,200 +1,388 @@
 pub mod messages;
+mod cell;
 mod glitcher;
+mod palette;
 
 use console::{ Console, InputType, ArrowType, KeyType };
+use console::layout::Layout;
 use console::curses::messages::bottom::BottomMessage;
+use console::curses::cell::Cell;
 use console::curses::glitcher::Glitcher;
-use game::Game;
+use console::curses::palette::Palette;
+use config::DEFAULT_TICK_RATE;
+use crash;
+use game::{ Game, GameError };
 use tile::Tile;
 use tile::base::Base;
-use tile::style::Style;
-use ui::Ui;
-use utils::rnd_lt;
+use tile::style::{ Style, Attr, FULL_LIGHT };
+use theme::{ Theme, Glyph };
+use ui::{ Ui, Menu, Panel, MessageType };
+use utils::fx_lt;
 
 use pancurses::*;
-use rand::prelude::*;
-
-use std::{thread, time};
-
-const CLR_BLACK: i16 = 0;
-const CLR_RED: i16 = 1;
-const CLR_GREEN: i16 = 2;
-const CLR_YELLOW: i16 = 3;
-const CLR_BLUE: i16 = 4;
-const CLR_MAGENTA: i16 = 5;
-const CLR_CYAN: i16 = 6;
-const CLR_WHITE: i16 = 7;
-
-const CLR_DARK1: i16 = 8;
-const CLR_DARK2: i16 = 9;
-const CLR_DARK3: i16 = 10;
-const CLR_DARK4: i16 = 11;
-const CLR_DARK5: i16 = 12;
-const CLR_DARK6: i16 = 13;
-const CLR_DARK7: i16 = 14;
-const CLR_DARK8: i16 = 15;
+
+use std::cmp;
+use std::mem;
+use std::time::{ Duration, Instant };
 
 pub struct CursesConsole {
     window: Window,
     message: Option<BottomMessage>,
+    menu: Option<Menu>,
+    panel: Option<Panel>,
+    status: Option<String>,
+    side: Option<Vec<String>>,
+    layout: Layout,
     frame: usize,
+
+    //  what each cell of the map showed last frame, None where it has to be
+    //  drawn again, the frame that was, and the boxes drawn on top of it
+    drawn: Vec<Option<Tile>>,
+    drawn_frame: usize,
+    boxes: Vec<(usize, usize, usize, usize)>,
+
+    //  picked once, so flickering tiles look different every run
+    noise: usize,
     glitcher: Glitcher,
-    use_colors: bool,
+    palette: Option<Palette>,
+    theme: Theme,
+
+    //  time between two ticks
+    tick: Duration,
+
+    //  whether the player asked to quit
+    quit: bool,
 }
 
 impl CursesConsole {
-    fn make_style(&self, style: &Style) -> chtype {
+    /// Changes how tiles are drawn from now on.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+        self.drawn.clear();
+    }
+
+    fn make_style(&mut self, style: &Style) -> chtype {
         let mut res = A_NORMAL;
 
-        if style.intersects(Style::STANDOUT) {
+        if style.attr.intersects(Attr::STANDOUT) {
             //  for some reason A_STANDOUT doesn't compile
             //  res |= A_STANDOUT;
         }
 
-        if style.intersects(Style::BOLD) {
+        if style.attr.intersects(Attr::BOLD) {
             res |= A_BOLD;
         }
 
-        if style.intersects(Style::UNDERLINE) {
+        if style.attr.intersects(Attr::UNDERLINE) {
             res |= A_UNDERLINE;
         }
 
-        if style.intersects(Style::DIM) {
+        if style.attr.intersects(Attr::DIM) {
             res |= A_DIM;
         }
 
-        if self.use_colors {
-            if style.intersects(Style::BLACK) {
-                res |= COLOR_PAIR(CLR_BLACK as u64);
-            }
+        if style.attr.intersects(Attr::REVERSE) {
+            res |= A_REVERSE;
+        }
 
-            if style.intersects(Style::RED) {
-                res |= COLOR_PAIR(CLR_RED as u64);
-            }
+        if style.attr.intersects(Attr::ITALIC) {
+            res |= A_ITALIC;
+        }
 
-            if style.intersects(Style::GREEN) {
-                res |= COLOR_PAIR(CLR_GREEN as u64);
-            }
+        if let Some(ref mut palette) = self.palette {
+            res |= palette.color_pair(style.fg, style.bg);
+        }
 
-            if style.intersects(Style::YELLOW) {
-                res |= COLOR_PAIR(CLR_YELLOW as u64);
-            }
+        res
+    }
 
-            if style.intersects(Style::BLUE) {
-                res |= COLOR_PAIR(CLR_BLUE as u64);
-            }
+    fn draw(&mut self, buffer: &Vec<Tile>) {
+        let area = self.layout.map;
+        let ww: usize = area.width;
 
-            if style.intersects(Style::MAGENTA) {
-                res |= COLOR_PAIR(CLR_MAGENTA as u64);
-            }
+        //  a buffer from before a resize may not fit
+        let len = cmp::min(buffer.len(), ww * area.height);
+        if self.drawn.len() != len {
+            self.drawn = vec![None; len];
+        }
 
-            if style.intersects(Style::CYAN) {
-                res |= COLOR_PAIR(CLR_CYAN as u64);
-            }
+        //  only tiles that changed since the last frame get drawn again, with
+        //  the walls next to them, which may join up differently now, and
+        //  animated tiles once a frame
+        let changed: Vec<bool> = (0..len)
+            .map(|i| self.drawn[i].as_ref() != Some(&buffer[i]))
+            .collect();
+        let new_frame = self.frame != self.drawn_frame;
+        self.drawn_frame = self.frame;
+
+        //  walls join up with neighbouring walls and doors
+        let is_wall = |i: Option<usize>| match i.and_then(|i| buffer.get(i)) {
+            Some(t) => t.has_base(&Base::Wall) || t.has_base(&Base::Door(0)),
+            None => false,
+        };
 
-            if style.intersects(Style::WHITE) {
-                res |= COLOR_PAIR(CLR_WHITE as u64);
-            }
+        for (i, tile) in buffer.iter().enumerate().take(len) {
+            let (x, y) = (i % ww, i / ww);
 
-            if style.intersects(Style::DARK1) {
-                res |= COLOR_PAIR(CLR_DARK1 as u64);
+            let near_change = tile.has_base(&Base::Wall) && (
+                (x > 0 && changed[i - 1]) ||
+                (x + 1 < ww && changed[i + 1]) ||
+                (i >= ww && changed[i - ww]) ||
+                (i + ww < len && changed[i + ww])
+            );
+            if !changed[i] && !near_change && !(new_frame && tile.anim.is_some()) {
+                continue;
             }
 
-            if style.intersects(Style::DARK2) {
-                res |= COLOR_PAIR(CLR_DARK2 as u64);
-            }
+            let glyph = match (&tile.cover, &tile.base) {
+                (&Some(ref cover), base) => self.theme.get_cover(cover)
+                    .unwrap_or_else(|| self.theme.get_base(base)),
+
+                (&None, &Base::Wall) => self.theme.get_wall(
+                    x > 0 && is_wall(Some(i - 1)),
+                    x + 1 < ww && is_wall(Some(i + 1)),
+                    is_wall(i.checked_sub(ww)),
+                    is_wall(Some(i + ww))
+                ),
+
+                //  npcs bring their own glyph, and so do items the theme
+                //  doesn't know
+                (&None, &Base::Npc(ch)) => Glyph::new(
+                    ch, self.theme.get_base(&tile.base).style
+                ),
+                (&None, &Base::Item(ch, kind)) => self.theme.get_item(kind)
+                    .unwrap_or_else(|| Glyph::new(ch, self.theme.get_base(&tile.base).style)),
+
+                (&None, base) => self.theme.get_base(base),
+            };
 
-            if style.intersects(Style::DARK3) {
-                res |= COLOR_PAIR(CLR_DARK3 as u64);
-            }
+            //  whatever the theme, the tile and its animation say, memories
+            //  are dim and still, and shadows dark
+            let style = tile.style.unwrap_or(glyph.style);
+            let style = match tile.anim {
+                Some(anim) if !tile.remembered =>
+                    anim.keyframe(self.frame, tile.place.0, tile.place.1, self.noise).apply(style),
+                _ => style,
+            };
+            let style = match (tile.remembered, tile.light < FULL_LIGHT) {
+                (true, _) => style.with_attr(Attr::DIM),
+                (false, true) => style.in_light(tile.light),
+                (false, false) => style,
+            };
+            let style = match tile.highlight {
+                true => style.with_attr(Attr::REVERSE),
+                false => style,
+            };
+            let attr = self.make_style(&style);
+            let written = self.glitcher.write(area.x + x, area.y + y, Cell::new(glyph.ch, attr));
+            self.drawn[i] = match written {
+                true => Some(tile.clone()),
+                false => None,
+            };
+        }
+    }
 
-            if style.intersects(Style::DARK4) {
-                res |= COLOR_PAIR(CLR_DARK4 as u64);
+    //  forgets what the map showed under given boxes, so that it gets drawn
+    //  again
+    fn uncover(&mut self, boxes: &[(usize, usize, usize, usize)]) {
+        let area = self.layout.map;
+
+        for &(bx, by, bw, bh) in boxes.iter() {
+            for y in cmp::max(by, area.y)..cmp::min(by + bh, area.y + area.height) {
+                for x in cmp::max(bx, area.x)..cmp::min(bx + bw, area.x + area.width) {
+                    if let Some(cell) = self.drawn.get_mut((y - area.y) * area.width + x - area.x) {
+                        *cell = None;
+                    }
+                }
             }
+        }
+    }
 
-            if style.intersects(Style::DARK5) {
-                res |= COLOR_PAIR(CLR_DARK5 as u64);
-            }
+    fn draw_message(&mut self) {
+        let mut text = match self.message {
+            Some(ref message) => String::from(message.get_text()),
+            None => {
+                let area = self.layout.messages;
+                if area.width >= 5 && area.height >= 3 {
+                    self.draw_box(area.x, area.y, area.width, area.height);
+                }
+                return;
+            },
+        };
 
-            if style.intersects(Style::DARK6) {
-                res |= COLOR_PAIR(CLR_DARK6 as u64);
-            }
+        //  show where typing goes
+        if self.message.as_ref().unwrap().is_prompt() {
+            text.push('_');
+        }
 
-            if style.intersects(Style::DARK7) {
-                res |= COLOR_PAIR(CLR_DARK7 as u64);
-            }
+        //  without a place of its own, the message goes over the bottom of
+        //  the map
+        let overlay = self.layout.messages.is_empty();
+        let area = match overlay {
+            true => self.layout.map,
+            false => self.layout.messages,
+        };
 
-            if style.intersects(Style::DARK8) {
-                res |= COLOR_PAIR(CLR_DARK8 as u64);
-            }
+        if area.width < 5 {
+            return;
         }
 
-        res
+        //  long messages grow the box upwards over the map, as far as its top
+        let mut lines = wrap(&text, area.width - 4);
+        let bottom = area.y + area.height;
+        let min_height = match overlay {
+            true => 3,
+            false => area.height,
+        };
+        let height = cmp::min(cmp::max(lines.len() + 2, min_height), bottom - self.layout.map.y);
+
+        if height < 3 {
+            return;
+        }
+
+        //  and if even that isn't enough, say there's more
+        if lines.len() > height - 2 {
+            lines.truncate(height - 2);
+            let mut last: Vec<char> = lines.pop().unwrap().chars().collect();
+            last.truncate((area.width - 4).saturating_sub(4));
+            lines.push(last.into_iter().collect::<String>() + " ...");
+        }
+
+        let top = bottom - height;
+        self.draw_box(area.x, top, area.width, height);
+
+        for (i, line) in lines.iter().enumerate() {
+            self.glitcher.write_str(area.x + 2, top + 1 + i, line);
+        }
     }
 
-    fn draw(&mut self, buffer: &Vec<Tile>) {
-        let mut x: usize = 0;
-        let mut y: usize = 0;
-        let ww: usize = self.get_width();
-
-        for tile in buffer.iter() {
-            let base = &tile.base;
-            let cover = &tile.cover;
-            let style = &tile.style;
-
-            let ch = match base {
-                &Base::Void => ' ',
-                &Base::Player => 'o',
-                &Base::Ground => '.',
-                &Base::Wall => 'X',
-                &Base::Water => '=',
-                &Base::Message(ref s) => '?',
-                &Base::Door(ref x) => 'd',
-            };
+    fn draw_status(&mut self) {
+        let area = self.layout.status;
+        let text = match self.status {
+            Some(ref text) if !area.is_empty() => format!(" {}", text),
+            _ => return,
+        };
 
-            let ch = (ch as chtype) | self.make_style(style);
-            self.glitcher.write(x, y, ch);
+        let chars: Vec<char> = text.chars().collect();
+        for x in 0..area.width {
+            let ch = chars.get(x).cloned().unwrap_or(' ');
+            self.glitcher.write(area.x + x, area.y, Cell::new(ch, A_REVERSE));
+        }
+    }
 
-            x += 1;
-            x %= ww;
-            if (x == 0) {
-                y += 1;
+    fn draw_side(&mut self) {
+        let area = self.layout.side;
+        let lines = match self.side.take() {
+            Some(lines) => lines,
+            None => return,
+        };
+
+        if area.width >= 5 && area.height >= 3 {
+            self.draw_box(area.x, area.y, area.width, area.height);
+
+            for (i, line) in lines.iter().take(area.height - 2).enumerate() {
+                let line: String = line.chars().take(area.width - 4).collect();
+                self.glitcher.write_str(area.x + 2, area.y + 1 + i, &line);
             }
         }
+
+        self.side = Some(lines);
     }
 
-    fn draw_message(&mut self) {
-        if let None = self.message {
-            return;
-        }
+    fn draw_menu(&mut self) {
+        let menu = match self.menu.take() {
+            Some(menu) => menu,
+            None => return,
+        };
 
-        let msg = self.message.as_ref().unwrap();
+        let ww = self.window.get_max_x() as usize;
+        let wh = self.window.get_max_y() as usize;
 
-        //  margins
-        let xm: usize = 1;
-        let ym: usize = 0;
+        let title_len = menu.title.chars().count();
+        let inner_w = menu.items.iter()
+            .map(|item| item.chars().count() + 4)
+            .fold(title_len, cmp::max) + 4;
+        let inner_h = menu.items.len() + 2;
 
-        let ww = self.get_width();
-        let wh = self.get_height();
+        //  if we don't have enough space to draw the menu, don't draw
+        if ww >= inner_w + 2 && wh >= inner_h + 2 {
+            let sx = (ww - inner_w - 2) / 2;
+            let sy = (wh - inner_h - 2) / 2;
+            self.draw_box(sx, sy, inner_w + 2, inner_h + 2);
 
-        let text = msg.get_text();
-        let text_width = ww - 2 - 2 * xm;
-        let mut num_lines = text.len() / text_width;
-        if text.len() % text_width > 0 {
-            num_lines += 1;
+            self.glitcher.write_str(
+                sx + 1 + (inner_w - title_len) / 2,
+                sy + 1,
+                &menu.title
+            );
+
+            for (i, item) in menu.items.iter().enumerate() {
+                let attr = match i == menu.selected {
+                    true => A_REVERSE,
+                    false => A_NORMAL,
+                };
+
+                let line = format!(" {} ", item);
+                for (j, ch) in line.chars().enumerate() {
+                    self.glitcher.write(sx + 3 + j, sy + 3 + i, Cell::new(ch, attr));
+                }
+            }
         }
 
-        //  if we don't have enough space to draw the message, don't draw
-        if wh < 2 + 2 * ym + num_lines {
-            return;
+        self.menu = Some(menu);
+    }
+
+    fn draw_panel(&mut self) {
+        let panel = match self.panel.take() {
+            Some(panel) => panel,
+            None => return,
+        };
+
+        //  the panel goes over the map
+        let area = self.layout.map;
+        let (sx, sy) = (area.x, area.y);
+
+        //  if we don't have enough space to draw the panel, don't draw
+        if area.width >= 8 && area.height >= 6 {
+            self.draw_box(sx, sy, area.width, area.height);
+
+            let text_width = area.width - 4;
+            let clip = |s: &str| s.chars().take(text_width).collect::<String>();
+
+            self.glitcher.write_str(sx + 2, sy + 1, &clip(&panel.title));
+
+            //  keep the selected line in view
+            let rows = area.height - 5;
+            let first = match panel.selected {
+                Some(sel) if sel >= rows => sel + 1 - rows,
+                _ => 0,
+            };
+
+            for (i, line) in panel.lines.iter().enumerate().skip(first).take(rows) {
+                let attr = match panel.selected == Some(i) {
+                    true => A_REVERSE,
+                    false => A_NORMAL,
+                };
+
+                for (j, ch) in clip(line).chars().enumerate() {
+                    self.glitcher.write(sx + 2 + j, sy + 3 + i - first, Cell::new(ch, attr));
+                }
+            }
+
+            self.glitcher.write_str(sx + 2, sy + area.height - 2, &clip(&panel.footer));
         }
 
-        let sx = 0;
-        let sy = wh - 2 - 2 * ym - num_lines;
+        self.panel = Some(panel);
+    }
+
+    fn draw_box(&mut self, sx: usize, sy: usize, w: usize, h: usize) {
+        let ex = sx + w - 1;
+        let ey = sy + h - 1;
+        self.boxes.push((sx, sy, w, h));
 
-        for y in sy..wh {
-            for x in sx..ww {
-                let ch = match (x == sx, x == ww - 1, y == sy, y == wh - 1) {
+        for y in sy..ey + 1 {
+            for x in sx..ex + 1 {
+                let ch = match (x == sx, x == ex, y == sy, y == ey) {
                     (true, _, true, _) => ACS_ULCORNER(),
                     (true, _, _, true) => ACS_LLCORNER(),
                     (_, true, true, _) => ACS_URCORNER(),
@@ -206,177 +394,245 @@ impl CursesConsole {
                     _ => ' ' as chtype,
                 };
 
-                self.glitcher.write(x, y, ch);
+                self.glitcher.write(x, y, Cell::from_chtype(ch));
             }
         }
+    }
 
-        for line in 0..num_lines {
-            let start = line * text_width;
-            let end = match line == num_lines - 1 {
-                true => start + text.len() % text_width,
-                false => (line + 1) * text_width,
-            };
+    /// Sets how many times per second the game and the glitches update.
+    pub fn set_tick_rate(&mut self, ticks_per_sec: u32) {
+        self.tick = Duration::from_secs(1) / cmp::max(ticks_per_sec, 1);
+    }
 
-            self.glitcher.write_str(
-                sx + 1 + xm,
-                sy + 1 + ym + line,
-                &text[start..end]
-            );
+    /// Sets how glitchy the screen gets, 0 for not at all.
+    pub fn set_glitch_level(&mut self, level: u8) {
+        self.glitcher.set_level(level);
+    }
+
+    fn read_input(&mut self, input: Input) -> Option<InputType> {
+        Some(match input {
+            Input::Character(ch) => InputType::Char(ch),
+
+            Input::KeyLeft => InputType::Arrow(ArrowType::Left),
+            Input::KeyRight => InputType::Arrow(ArrowType::Right),
+            Input::KeyUp => InputType::Arrow(ArrowType::Up),
+            Input::KeyDown => InputType::Arrow(ArrowType::Down),
+
+            Input::KeyF0 => InputType::Func(0),
+            Input::KeyF1 => InputType::Func(1),
+            Input::KeyF2 => InputType::Func(2),
+            Input::KeyF3 => InputType::Func(3),
+            Input::KeyF4 => InputType::Func(4),
+            Input::KeyF5 => InputType::Func(5),
+            Input::KeyF6 => InputType::Func(6),
+            Input::KeyF7 => InputType::Func(7),
+            Input::KeyF8 => InputType::Func(8),
+            Input::KeyF9 => InputType::Func(9),
+            Input::KeyF10 => InputType::Func(10),
+            Input::KeyF11 => InputType::Func(11),
+            Input::KeyF12 => InputType::Func(12),
+            Input::KeyF13 => InputType::Func(13),
+            Input::KeyF14 => InputType::Func(14),
+            Input::KeyF15 => InputType::Func(15),
+
+            Input::KeyBackspace => InputType::Key(KeyType::Backspace),
+            Input::KeyEnter => InputType::Key(KeyType::Enter),
+            Input::KeyHome => InputType::Key(KeyType::Home),
+            Input::KeyEnd => InputType::Key(KeyType::End),
+
+            Input::KeyResize => {
+                resize_term(0, 0);
+                self.window.clear();
+
+                let nww = self.window.get_max_x() as usize;
+                let nwh = self.window.get_max_y() as usize;
+                self.glitcher.resize(nww, nwh);
+                self.drawn.clear();
+
+                //  the game only ever hears about the map
+                self.layout = Layout::new(nww, nwh);
+                InputType::Resize(self.layout.map.width as u32, self.layout.map.height as u32)
+            },
+
+            _ => return None,
+        })
+    }
+
+    fn show_uis(&mut self, uis: Vec<Ui>) {
+        for ui in uis.into_iter() {
+            match ui {
+                Ui::Message(MessageType::Static, _, s) => {
+                    self.message = Some(BottomMessage::new(s));
+                },
+
+                Ui::Message(MessageType::Prompt, _, s) => {
+                    self.message = Some(BottomMessage::prompt(s));
+                },
+
+                Ui::Menu(menu) => {
+                    self.menu = menu;
+                },
+
+                Ui::Panel(panel) => {
+                    self.panel = panel;
+                },
+
+                Ui::Status(text) => {
+                    self.status = Some(text);
+                },
+
+                Ui::Side(lines) => {
+                    self.side = Some(lines);
+                },
+
+                Ui::Clear => {
+                    self.message = None;
+                    self.menu = None;
+                    self.panel = None;
+                    self.status = None;
+                    self.side = None;
+                },
+
+                Ui::Quit => {
+                    self.quit = true;
+                },
+            }
         }
     }
 
+    fn redraw<G>(&mut self, game: &mut G) -> Result<(), GameError> where G: Game {
+        let buffer = game.gen_buffer()?;
+        let boxes = mem::replace(&mut self.boxes, Vec::new());
+        self.draw_all(buffer);
 
+        //  wherever a box went away, the map has to show again
+        if self.boxes != boxes {
+            self.uncover(&boxes);
+            self.boxes.clear();
+            self.draw_all(buffer);
+        }
+
+        self.glitcher.compose();
+        self.glitcher.present(&self.window);
+        Ok(())
+    }
+
+    fn draw_all(&mut self, buffer: Option<&Vec<Tile>>) {
+        if let Some(buffer) = buffer {
+            self.draw(buffer);
+        }
+
+        self.draw_status();
+        self.draw_side();
+        self.draw_message();
+        self.draw_panel();
+        self.draw_menu();
+    }
 
     fn init_colors(&mut self) {
-        if
-            has_colors() &&
-            can_change_color() &&
-            COLORS() >= 16 &&
-            COLOR_PAIRS() >= 16
-        {
-            self.use_colors = true;
-
-            init_color(CLR_DARK1, 111, 111, 111);
-            init_color(CLR_DARK2, 222, 222, 222);
-            init_color(CLR_DARK3, 333, 333, 333);
-            init_color(CLR_DARK4, 444, 444, 444);
-            init_color(CLR_DARK5, 555, 555, 555);
-            init_color(CLR_DARK6, 666, 666, 666);
-            init_color(CLR_DARK7, 777, 777, 777);
-            init_color(CLR_DARK8, 888, 888, 888);
-
-            init_pair(CLR_BLACK,    CLR_BLACK,      CLR_BLACK);
-            init_pair(CLR_RED,      CLR_RED,        CLR_BLACK);
-            init_pair(CLR_GREEN,    CLR_GREEN,      CLR_BLACK);
-            init_pair(CLR_YELLOW,   CLR_YELLOW,     CLR_BLACK);
-            init_pair(CLR_BLUE,     CLR_BLUE,       CLR_BLACK);
-            init_pair(CLR_MAGENTA,  CLR_MAGENTA,    CLR_BLACK);
-            init_pair(CLR_CYAN,     CLR_CYAN,       CLR_BLACK);
-            init_pair(CLR_WHITE,    CLR_WHITE,      CLR_BLACK);
-
-            init_pair(CLR_DARK1,    CLR_DARK1,      CLR_BLACK);
-            init_pair(CLR_DARK2,    CLR_DARK2,      CLR_BLACK);
-            init_pair(CLR_DARK3,    CLR_DARK3,      CLR_BLACK);
-            init_pair(CLR_DARK4,    CLR_DARK4,      CLR_BLACK);
-            init_pair(CLR_DARK5,    CLR_DARK5,      CLR_BLACK);
-            init_pair(CLR_DARK6,    CLR_DARK6,      CLR_BLACK);
-            init_pair(CLR_DARK7,    CLR_DARK7,      CLR_BLACK);
-            init_pair(CLR_DARK8,    CLR_DARK8,      CLR_BLACK);
+        if has_colors() {
+            start_color();
+            self.palette = Some(Palette::new());
         }
     }
 }
 
 impl Console for CursesConsole {
     fn new() -> Self {
+        //  pancurses does this too, but hands setlocale a dangling pointer;
+        //  without it ncurses won't draw anything outside of ASCII
+        unsafe {
+            libc::setlocale(libc::LC_ALL, b"\0".as_ptr() as *const libc::c_char);
+        }
+
         let window = initscr();
         let ww = window.get_max_x() as usize;
-        let wh = window.get_max_x() as usize;
+        let wh = window.get_max_y() as usize;
 
         let mut res = Self {
             window: window,
             message: None,
+            menu: None,
+            panel: None,
+            status: None,
+            side: None,
+            layout: Layout::new(ww, wh),
             frame: 0,
+            drawn: Vec::new(),
+            drawn_frame: 0,
+            boxes: Vec::new(),
+            noise: fx_lt(usize::max_value()),
             glitcher: Glitcher::new(ww, wh),
-            use_colors: false,
+            palette: None,
+            theme: Theme::classic(),
+            tick: Duration::from_secs(1) / DEFAULT_TICK_RATE,
+            quit: false,
         };
 
         raw();
         noecho();
-        start_color();
         res.window.keypad(true);
-        res.window.nodelay(true);
 
         res.init_colors();
 
         res
     }
 
-    fn render<G>(&mut self, mut game: G) where G: Game {
-        loop {
-            let res;
-
-            if self.frame == 0 {
-                res = InputType::FirstFrame;
-            }
-            else {
-                res = match self.window.getch() {
-                    Some(Input::Character(ch)) => InputType::Char(ch),
-
-                    Some(Input::KeyLeft) => InputType::Arrow(ArrowType::Left),
-                    Some(Input::KeyRight) => InputType::Arrow(ArrowType::Right),
-                    Some(Input::KeyUp) => InputType::Arrow(ArrowType::Up),
-                    Some(Input::KeyDown) => InputType::Arrow(ArrowType::Down),
-
-                    Some(Input::KeyF0) => InputType::Func(0),
-                    Some(Input::KeyF1) => InputType::Func(1),
-                    Some(Input::KeyF2) => InputType::Func(2),
-                    Some(Input::KeyF3) => InputType::Func(3),
-                    Some(Input::KeyF4) => InputType::Func(4),
-                    Some(Input::KeyF5) => InputType::Func(5),
-                    Some(Input::KeyF6) => InputType::Func(6),
-                    Some(Input::KeyF7) => InputType::Func(7),
-                    Some(Input::KeyF8) => InputType::Func(8),
-                    Some(Input::KeyF9) => InputType::Func(9),
-                    Some(Input::KeyF10) => InputType::Func(10),
-                    Some(Input::KeyF11) => InputType::Func(11),
-                    Some(Input::KeyF12) => InputType::Func(12),
-                    Some(Input::KeyF13) => InputType::Func(13),
-                    Some(Input::KeyF14) => InputType::Func(14),
-                    Some(Input::KeyF15) => InputType::Func(15),
-
-                    Some(Input::KeyBackspace) =>
-                        InputType::Key(KeyType::Backspace),
-                    Some(Input::KeyEnter) =>
-                        InputType::Key(KeyType::Enter),
-                    Some(Input::KeyHome) =>
-                        InputType::Key(KeyType::Home),
-                    Some(Input::KeyEnd) =>
-                        InputType::Key(KeyType::End),
-
-                    Some(Input::KeyResize) => {
-                        resize_term(0, 0);
-
-                        let nww = self.window.get_max_x();
-                        let nwh = self.window.get_max_y();
-                        self.glitcher.resize(nww as usize, nwh as usize);
-                        InputType::Resize(nww as u32, nwh as u32)
-                    },
-
-                    _ => InputType::Char('a'),
-                };
-            }
+    fn render<G>(&mut self, mut game: G) -> Result<(), GameError> where G: Game {
+        crash::note_input(&InputType::FirstFrame);
+        let uis = game.react(InputType::FirstFrame)?;
+        self.show_uis(uis);
+        self.redraw(&mut game)?;
+
+        let mut last_tick = Instant::now();
+        let mut next_tick = last_tick + self.tick;
+
+        while !self.quit {
+            //  block until either a key arrives or the next tick is due
+            let now = Instant::now();
+            let wait = match next_tick > now {
+                true => next_tick - now,
+                false => Duration::from_millis(0),
+            };
+            self.window.timeout(
+                (wait.as_secs() * 1000 + wait.subsec_millis() as u64) as i32
+            );
 
-            let uis = game.react(res);
-            for ui in uis.into_iter() {
-                match ui {
-                    Ui::Message(_, _, s) => {
-                        self.message = Some(BottomMessage::new(s));
-                    },
-                }
+            let input = self.window.getch();
+            if let Some(input) = input.and_then(|i| self.read_input(i)) {
+                crash::note_input(&input);
+                let uis = game.react(input)?;
+                self.show_uis(uis);
             }
 
-            let buffer = game.gen_buffer();
-            buffer.as_ref().map(|buf| {
-                self.draw(buf);
-            });
-
-            self.draw_message();
+            let now = Instant::now();
+            if now >= next_tick {
+                let uis = game.tick(now - last_tick)?;
+                self.show_uis(uis);
+                self.glitcher.update();
+                self.frame += 1;
 
-            self.glitcher.update();
-            self.glitcher.render(&self.window);
+                last_tick = now;
+                next_tick += self.tick;
 
-            self.frame += 1;
+                //  if we fell far behind, don't try to catch up
+                if next_tick < now {
+                    next_tick = now + self.tick;
+                }
+            }
 
-            thread::sleep(time::Duration::from_millis(10));
+            self.redraw(&mut game)?;
         }
+
+        Ok(())
     }
 
     fn get_width(&self) -> usize {
-        self.window.get_max_x() as usize
+        self.layout.map.width
     }
 
     fn get_height(&self) -> usize {
-        self.window.get_max_y() as usize
+        self.layout.map.height
     }
 }
 
@@ -385,3 +641,38 @@ impl Drop for CursesConsole {
         endwin();
     }
 }
+
+//  splits given text into lines no longer than given width, between words
+//  where it can
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut res: Vec<String> = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split(' ') {
+        let mut word: Vec<char> = word.chars().collect();
+
+        //  words too long for a line of their own get cut up
+        while word.len() > width {
+            if !line.is_empty() {
+                res.push(mem::replace(&mut line, String::new()));
+            }
+            res.push(word.drain(..width).collect());
+        }
+
+        let len = line.chars().count();
+        if len > 0 && len + 1 + word.len() > width {
+            res.push(mem::replace(&mut line, String::new()));
+        }
+
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.extend(word);
+    }
+
+    if !line.is_empty() {
+        res.push(line);
+    }
+
+    res
+}
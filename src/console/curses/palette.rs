@@ -0,0 +1,201 @@
+//  maps `Color`s onto curses colors and color pairs, downgrading them to
+//  whatever the terminal supports
+
+use tile::style::Color;
+
+use pancurses::*;
+
+use std::cmp;
+use std::collections::HashMap;
+
+/// How many colors the terminal can show.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum ColorMode {
+    Mono,
+    Ansi8,
+    Ansi16,
+    Indexed256,
+    TrueColor,
+}
+
+//  pairs 1 through 7 are always the 8 basic colors on the default background,
+//  so the glitcher can refer to them directly; the rest are handed out on
+//  demand
+const FIXED_PAIRS: i16 = 8;
+
+pub struct Palette {
+    mode: ColorMode,
+    default_colors: bool,
+
+    pairs: HashMap<(i16, i16), i16>,
+    next_pair: i16,
+    max_pairs: i16,
+
+    //  only used in truecolor mode, where we define colors of our own past
+    //  the 256 xterm ones, which themes may refer to by index
+    colors: HashMap<(u8, u8, u8), i16>,
+    next_color: i16,
+    max_colors: i16,
+}
+
+impl Palette {
+    /// Returns a Palette for the current terminal. Must be called after
+    /// `start_color()`.
+    pub fn new() -> Self {
+        let num_colors = COLORS();
+
+        let mode = match (has_colors(), num_colors) {
+            (false, _) => ColorMode::Mono,
+            (true, n) if n > 256 && can_change_color() => ColorMode::TrueColor,
+            (true, n) if n >= 256 => ColorMode::Indexed256,
+            (true, n) if n >= 16 => ColorMode::Ansi16,
+            _ => ColorMode::Ansi8,
+        };
+
+        //  a chtype only has room for 256 color pairs
+        let max_pairs = cmp::min(COLOR_PAIRS(), 256) as i16;
+
+        let mut res = Self {
+            mode: mode,
+            default_colors: false,
+            pairs: HashMap::new(),
+            next_pair: FIXED_PAIRS,
+            max_pairs: max_pairs,
+            colors: HashMap::new(),
+            next_color: 256,
+            max_colors: cmp::min(num_colors, i16::max_value() as i32) as i16,
+        };
+
+        if mode != ColorMode::Mono {
+            res.default_colors = use_default_colors() != ERR;
+
+            let bg = res.default_bg();
+            for x in 1..FIXED_PAIRS {
+                init_pair(x, x, bg);
+            }
+        }
+
+        res
+    }
+
+    /// Returns the attribute that selects given foreground and background.
+    pub fn color_pair(&mut self, fg: Color, bg: Color) -> chtype {
+        if self.mode == ColorMode::Mono {
+            return A_NORMAL;
+        }
+
+        let (fg, bold) = self.color_index(fg, true);
+        let (bg, _) = self.color_index(bg, false);
+
+        let pair = match (fg, bg) {
+            (-1, -1) => 0,
+            (x, b) if x > 0 && x < FIXED_PAIRS && b == self.default_bg() => x,
+            key => self.pair_index(key),
+        };
+
+        let mut res = COLOR_PAIR(pair as chtype);
+        if bold {
+            res |= A_BOLD;
+        }
+        res
+    }
+
+    fn default_bg(&self) -> i16 {
+        match self.default_colors {
+            true => -1,
+            false => COLOR_BLACK,
+        }
+    }
+
+    fn default_fg(&self) -> i16 {
+        match self.default_colors {
+            true => -1,
+            false => COLOR_WHITE,
+        }
+    }
+
+    //  returns the curses color for given color, and whether it needs bold to
+    //  look bright because the terminal only has 8 colors
+    fn color_index(&mut self, color: Color, is_fg: bool) -> (i16, bool) {
+        if color == Color::Default {
+            return match is_fg {
+                true => (self.default_fg(), false),
+                false => (self.default_bg(), false),
+            };
+        }
+
+        match self.mode {
+            ColorMode::Mono => (-1, false),
+
+            ColorMode::Ansi8 => match color.to_ansi() {
+                Color::Ansi(x) if x >= 8 => ((x - 8) as i16, is_fg),
+                Color::Ansi(x) => (x as i16, false),
+                _ => (-1, false),
+            },
+
+            ColorMode::Ansi16 => match color.to_ansi() {
+                Color::Ansi(x) => (x as i16, false),
+                _ => (-1, false),
+            },
+
+            ColorMode::Indexed256 => match color.to_indexed() {
+                Color::Indexed(x) => (x as i16, false),
+                _ => (-1, false),
+            },
+
+            ColorMode::TrueColor => match color {
+                Color::Rgb(r, g, b) => (self.rgb_index(r, g, b), false),
+                c => match c.to_indexed() {
+                    Color::Indexed(x) => (x as i16, false),
+                    _ => (-1, false),
+                },
+            },
+        }
+    }
+
+    fn rgb_index(&mut self, r: u8, g: u8, b: u8) -> i16 {
+        if let Some(&x) = self.colors.get(&(r, g, b)) {
+            return x;
+        }
+
+        //  out of colors; changing one would change cells already on screen,
+        //  so make do with the closest xterm color
+        if self.next_color >= self.max_colors {
+            return match Color::Rgb(r, g, b).to_indexed() {
+                Color::Indexed(x) => x as i16,
+                _ => -1,
+            };
+        }
+
+        let x = self.next_color;
+        self.next_color += 1;
+
+        let scale = |v: u8| (v as i32 * 1000 / 255) as i16;
+        init_color(x, scale(r), scale(g), scale(b));
+        self.colors.insert((r, g, b), x);
+        x
+    }
+
+    fn pair_index(&mut self, key: (i16, i16)) -> i16 {
+        if let Some(&x) = self.pairs.get(&key) {
+            return x;
+        }
+
+        if self.max_pairs <= FIXED_PAIRS {
+            return 0;
+        }
+
+        //  out of pairs, start reusing them from the beginning
+        if self.next_pair >= self.max_pairs {
+            self.pairs.clear();
+            self.next_pair = FIXED_PAIRS;
+        }
+
+        let x = self.next_pair;
+        self.next_pair += 1;
+
+        init_pair(x, key.0, key.1);
+        self.pairs.insert(key, x);
+        x
+    }
+}
@@ -0,0 +1,75 @@
+//  how the screen is split up: a status bar along the top, messages along the
+//  bottom, and the map in between, with a side panel on its right when the
+//  screen is wide enough. Small screens lose the side panel first, then the
+//  status bar and the messages, so the map always gets something
+
+use std::cmp;
+
+/// A rectangle of the screen, in tiles.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    pub fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self {
+            x: x,
+            y: y,
+            width: width,
+            height: height,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
+/// Rows for the status bar and the message box, and columns for the side
+/// panel.
+const STATUS_HEIGHT: usize = 1;
+const MESSAGE_HEIGHT: usize = 5;
+const SIDE_WIDTH: usize = 24;
+
+/// The map keeps at least this much of the screen before anything else gets
+/// any.
+const MIN_MAP_WIDTH: usize = 40;
+const MIN_MAP_HEIGHT: usize = 8;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct Layout {
+    pub map: Rect,
+    pub status: Rect,
+    pub messages: Rect,
+    pub side: Rect,
+}
+
+impl Layout {
+    /// Returns the layout for a screen of given size.
+    pub fn new(width: usize, height: usize) -> Self {
+        let (status_h, message_h) = match height >= MIN_MAP_HEIGHT + STATUS_HEIGHT + MESSAGE_HEIGHT {
+            true => (STATUS_HEIGHT, MESSAGE_HEIGHT),
+            false => (0, 0),
+        };
+
+        let side_w = match width >= MIN_MAP_WIDTH + SIDE_WIDTH {
+            true => SIDE_WIDTH,
+            false => 0,
+        };
+
+        //  the map never goes below a single tile, even on a silly screen
+        let map_w = cmp::max(width - side_w, 1);
+        let map_h = cmp::max(height - status_h - message_h, 1);
+
+        Self {
+            status: Rect::new(0, 0, width, status_h),
+            map: Rect::new(0, status_h, map_w, map_h),
+            side: Rect::new(map_w, status_h, side_w, map_h),
+            messages: Rect::new(0, status_h + map_h, width, message_h),
+        }
+    }
+}
@@ -1,18 +1,22 @@
 pub mod curses;
+pub mod layout;
 
-use game::Game;
+use game::{ Game, GameError };
 
 /// Indicates the direction of an arrow key.
+#[derive(Debug)]
 pub enum ArrowType {
     Left, Right, Up, Down,
 }
 
 /// Indicates the type of a key.
+#[derive(Debug)]
 pub enum KeyType {
     Backspace, Enter, Home, End,
 }
 
 /// Indicates the type of an input.
+#[derive(Debug)]
 pub enum InputType {
     FirstFrame,
     Char(char),
@@ -27,10 +31,11 @@ pub trait Console {
     /// Returns a new Console.
     fn new() -> Self;
 
-    /// Enters a rendering loop, consuming a Game.
-    fn render<G>(&mut self, game: G) where G: Game;
+    /// Enters a rendering loop, consuming a Game. Returns once the Game is
+    /// over, or with the error that stopped it.
+    fn render<G>(&mut self, game: G) -> Result<(), GameError> where G: Game;
 
-    /// Gets width and height of the console, in tiles.
+    /// Gets width and height of the map viewport, in tiles.
     fn get_width(&self) -> usize;
     fn get_height(&self) -> usize;
 }
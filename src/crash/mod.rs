@@ -0,0 +1,100 @@
+//  what to leave behind when the game falls over: the terminal the way it was
+//  before, and a report with the seed and the last few inputs, so that
+//  whatever happened can be played again with `--seed`
+
+use console::InputType;
+
+use pancurses::endwin;
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::env;
+use std::fs;
+use std::io;
+use std::panic;
+use std::path::PathBuf;
+use std::time::{ SystemTime, UNIX_EPOCH };
+
+/// How many inputs a report remembers.
+const MAX_INPUTS: usize = 64;
+
+struct Log {
+    seed: Option<u64>,
+    inputs: VecDeque<String>,
+}
+
+thread_local! {
+    static LOG: RefCell<Log> = RefCell::new(Log {
+        seed: None,
+        inputs: VecDeque::new(),
+    });
+}
+
+/// Remembers the seed of the game being played.
+pub fn set_seed(seed: u64) {
+    LOG.with(|log| log.borrow_mut().seed = Some(seed));
+}
+
+/// Remembers given input, forgetting the oldest one if there are too many.
+pub fn note_input(input: &InputType) {
+    LOG.with(|log| {
+        let mut log = log.borrow_mut();
+        if log.inputs.len() == MAX_INPUTS {
+            log.inputs.pop_front();
+        }
+        log.inputs.push_back(format!("{:?}", input));
+    });
+}
+
+/// Writes a report saying the game stopped for given reason, and returns
+/// where it went: the current directory if possible, else the temp one.
+pub fn write_report(reason: &str) -> io::Result<PathBuf> {
+    let mut text = String::from("oaendlig crashed\n\n");
+    text.push_str(&format!("reason: {}\n", reason));
+    text.push_str(&format!("arguments: {}\n", env::args().skip(1).collect::<Vec<_>>().join(" ")));
+
+    //  the log may be in use by whatever panicked
+    LOG.with(|log| match log.try_borrow() {
+        Ok(log) => {
+            match log.seed {
+                Some(seed) => text.push_str(&format!("seed: {} (play it again with --seed {})\n", seed, seed)),
+                None => text.push_str("seed: none yet\n"),
+            }
+
+            text.push_str("\nlast inputs, oldest first:\n");
+            for input in log.inputs.iter() {
+                text.push_str(&format!("    {}\n", input));
+            }
+        },
+        Err(_) => text.push_str("seed and inputs: unavailable\n"),
+    });
+
+    let secs = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    let name = format!("oaendlig-crash-{}.txt", secs);
+
+    let path = PathBuf::from(&name);
+    match fs::write(&path, &text) {
+        Ok(()) => Ok(path),
+        Err(_) => {
+            let path = env::temp_dir().join(&name);
+            fs::write(&path, &text)?;
+            Ok(path)
+        },
+    }
+}
+
+/// Makes panics put the terminal back and write a report, before the usual
+/// message. Only worth it once the terminal has been taken over.
+pub fn install_hook() {
+    let default = panic::take_hook();
+
+    panic::set_hook(Box::new(move |info| {
+        endwin();
+        default(info);
+
+        match write_report(&info.to_string()) {
+            Ok(path) => eprintln!("crash report written to {}", path.display()),
+            Err(e) => eprintln!("could not write a crash report: {}", e),
+        }
+    }));
+}
@@ -0,0 +1,160 @@
+//  which part of the world the map shows. Every mode works out a target
+//  corner for the view each frame; look-ahead glides towards it over a few
+//  ticks, everything else jumps straight there. Coming into a new room always
+//  jumps
+
+use uuid::Uuid;
+
+use std::time::Duration;
+
+/// How long look-ahead takes to catch up with where it wants to be, roughly.
+const GLIDE: Duration = Duration::from_millis(250);
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum CameraMode {
+    /// The player always in the middle.
+    Follow,
+
+    /// The room in the middle if it fits, otherwise the player.
+    Room,
+
+    /// Still until the player gets close to an edge.
+    Deadzone,
+
+    /// Ahead of the player, in the direction they last moved.
+    LookAhead,
+}
+
+impl CameraMode {
+    pub const ALL: [CameraMode; 4] = [
+        CameraMode::Follow, CameraMode::Room, CameraMode::Deadzone, CameraMode::LookAhead,
+    ];
+
+    /// Returns the name config files use for this mode.
+    pub fn key(&self) -> &'static str {
+        match self {
+            &CameraMode::Follow => "follow",
+            &CameraMode::Room => "room",
+            &CameraMode::Deadzone => "deadzone",
+            &CameraMode::LookAhead => "look-ahead",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        CameraMode::ALL.iter().find(|mode| mode.key() == key).cloned()
+    }
+
+    /// Returns the mode after this one, going round.
+    pub fn next(&self) -> Self {
+        let i = CameraMode::ALL.iter().position(|mode| mode == self).unwrap();
+        CameraMode::ALL[(i + 1) % CameraMode::ALL.len()]
+    }
+}
+
+pub struct Camera {
+    mode: CameraMode,
+
+    //  top left corner of the view, in tiles of the player's room, and where
+    //  it is headed
+    x: f32,
+    y: f32,
+    target: (i32, i32),
+
+    //  the room the view was last in, and the way the player last moved
+    room: Option<Uuid>,
+    heading: (i32, i32),
+}
+
+impl Camera {
+    pub fn new(mode: CameraMode) -> Self {
+        Self {
+            mode: mode,
+            x: 0.0,
+            y: 0.0,
+            target: (0, 0),
+            room: None,
+            heading: (0, 0),
+        }
+    }
+
+    pub fn get_mode(&self) -> CameraMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        self.mode = mode;
+        self.room = None;
+    }
+
+    /// Notes which way the player just moved.
+    pub fn moved(&mut self, dx: i32, dy: i32) {
+        self.heading = (dx.signum(), dy.signum());
+    }
+
+    /// Returns the top left corner of a view of given size, onto a room of
+    /// given size with the player at given position.
+    pub fn view(
+        &mut self,
+        room: &Uuid,
+        player: (i32, i32),
+        room_size: (i32, i32),
+        view: (i32, i32)
+    ) -> (i32, i32) {
+        let snap = self.room != Some(room.clone());
+        let (cx, cy) = (self.x.round() as i32, self.y.round() as i32);
+
+        let tx = target(self.mode, cx, player.0, room_size.0, view.0, self.heading.0, snap);
+        let ty = target(self.mode, cy, player.1, room_size.1, view.1, self.heading.1, snap);
+        self.target = (tx, ty);
+
+        if snap || self.mode != CameraMode::LookAhead {
+            self.x = tx as f32;
+            self.y = ty as f32;
+            self.room = Some(room.clone());
+        }
+
+        (self.x.round() as i32, self.y.round() as i32)
+    }
+
+    /// Glides towards the target, given the time since the last tick, and
+    /// returns whether the view moved.
+    pub fn tick(&mut self, elapsed: Duration) -> bool {
+        let before = (self.x.round() as i32, self.y.round() as i32);
+        let glide = GLIDE.as_secs() as f32 + GLIDE.subsec_nanos() as f32 / 1e9;
+        let elapsed = elapsed.as_secs() as f32 + elapsed.subsec_nanos() as f32 / 1e9;
+        let share = (elapsed / glide).min(1.0);
+
+        self.x += (self.target.0 as f32 - self.x) * share;
+        self.y += (self.target.1 as f32 - self.y) * share;
+
+        before != (self.x.round() as i32, self.y.round() as i32)
+    }
+}
+
+//  where the view should start along one axis, given where it starts now, the
+//  player's position, the room size, the view size and the player's heading
+fn target(mode: CameraMode, cur: i32, pos: i32, size: i32, view: i32, heading: i32, snap: bool) -> i32 {
+    let centered = pos - view / 2;
+
+    match mode {
+        CameraMode::Follow => centered,
+
+        CameraMode::Room => match size <= view {
+            true => (size - view) / 2,
+            false => centered,
+        },
+
+        //  keep the player a quarter of the view away from the edges
+        CameraMode::Deadzone => {
+            let margin = view / 4;
+            match pos - cur {
+                _ if snap => centered,
+                p if p < margin => pos - margin,
+                p if p > view - 1 - margin => pos - (view - 1 - margin),
+                _ => cur,
+            }
+        },
+
+        CameraMode::LookAhead => centered + heading * view / 4,
+    }
+}
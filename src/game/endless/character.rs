@@ -0,0 +1,70 @@
+//  who the player is: a name typed in at the start of a run, and a few traits
+//  picked from a list, each changing some small thing about how the house
+//  plays
+
+/// The longest name the player can type.
+pub const MAX_NAME: usize = 20;
+
+/// How many traits the player can pick.
+pub const MAX_TRAITS: usize = 2;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Trait {
+    SharpEyed,
+    Quick,
+    PackRat,
+    Surveyor,
+}
+
+impl Trait {
+    pub const ALL: [Trait; 4] = [
+        Trait::SharpEyed, Trait::Quick, Trait::PackRat, Trait::Surveyor,
+    ];
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            &Trait::SharpEyed => "sharp-eyed",
+            &Trait::Quick => "quick",
+            &Trait::PackRat => "pack rat",
+            &Trait::Surveyor => "surveyor",
+        }
+    }
+
+    pub fn get_description(&self) -> &'static str {
+        match self {
+            &Trait::SharpEyed => "sees a little further",
+            &Trait::Quick => "moves a little faster",
+            &Trait::PackRat => "starts out with a few odds and ends",
+            &Trait::Surveyor => "knows where the doors of the first room lead",
+        }
+    }
+}
+
+/// The character being made, until the player is done with it.
+pub struct Creation {
+    pub name: String,
+
+    //  the selected trait once the name is in, None while typing it
+    pub selected: Option<usize>,
+    pub traits: Vec<Trait>,
+}
+
+impl Creation {
+    pub fn new() -> Self {
+        Self {
+            name: String::new(),
+            selected: None,
+            traits: Vec::new(),
+        }
+    }
+
+    /// Picks given trait, or unpicks it if it was picked. Does nothing if
+    /// there is no room for another one.
+    pub fn toggle(&mut self, t: Trait) {
+        match self.traits.iter().position(|&picked| picked == t) {
+            Some(i) => { self.traits.remove(i); },
+            None if self.traits.len() < MAX_TRAITS => { self.traits.push(t); },
+            None => (),
+        }
+    }
+}
@@ -0,0 +1,305 @@
+//  dumps the world for debugging and sharing: every room with its size, kind
+//  and doors, and the links between doors, as a Graphviz graph, as JSON, or
+//  as a standalone HTML page with each room drawn out in ASCII
+
+use game::endless::{ EndlessGame, RoomDoor, Item };
+use game::endless::room::Room;
+use game::GameError;
+use theme::Theme;
+use tile::base::Base;
+
+use uuid::Uuid;
+
+use std::fmt::Write;
+use std::path::Path;
+
+/// What to export the world as.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum Format {
+    Dot,
+    Json,
+    Html,
+}
+
+impl Format {
+    /// Returns the format that goes with the extension of given path.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let ext = path.as_ref().extension()?.to_str()?.to_lowercase();
+
+        match ext.as_str() {
+            "dot" | "gv" => Some(Format::Dot),
+            "json" => Some(Format::Json),
+            "html" | "htm" => Some(Format::Html),
+            _ => None,
+        }
+    }
+}
+
+impl EndlessGame {
+    /// Returns the world in given format, as the house of the player with
+    /// given name.
+    pub fn export(&self, format: Format, name: &str) -> Result<String, GameError> {
+        match format {
+            Format::Dot => Ok(self.to_dot(name)),
+            Format::Json => Ok(self.to_json(name)),
+            Format::Html => self.to_html(name),
+        }
+    }
+
+    //  rooms sorted by uuid, so the same world always exports the same
+    fn sorted_rooms(&self) -> Vec<&Box<dyn Room>> {
+        let mut rooms: Vec<&Box<dyn Room>> = self.rooms.values().collect();
+        rooms.sort_by_key(|room| room.get_uuid());
+        rooms
+    }
+
+    fn sorted_links(&self) -> Vec<(&RoomDoor, &RoomDoor)> {
+        let mut links: Vec<(&RoomDoor, &RoomDoor)> = self.links.iter().collect();
+        links.sort();
+        links
+    }
+
+    fn to_dot(&self, name: &str) -> String {
+        let mut res = String::new();
+
+        writeln!(res, "digraph world {{").unwrap();
+        writeln!(res, "    label=\"{}'s house\";", quoted(name)).unwrap();
+        writeln!(res, "    node [shape=box];").unwrap();
+
+        for room in self.sorted_rooms() {
+            let uuid = room.get_uuid();
+            writeln!(
+                res,
+                "    \"{}\" [label=\"{}\\n{} {}x{}\"{}];",
+                uuid.simple(),
+                quoted(self.room_name(&uuid)),
+                room.get_kind(),
+                room.get_width(),
+                room.get_height(),
+                match uuid == *self.player.get_room() {
+                    true => format!(", xlabel=\"{}\", style=bold", quoted(name)),
+                    false => String::new(),
+                }
+            ).unwrap();
+        }
+
+        //  links that lead back are one edge both ways, the odd ones dashed
+        //  arrows
+        for (from, to) in self.sorted_links() {
+            let two_way = self.links.get(to) == Some(from);
+            if two_way && from > to {
+                continue;
+            }
+
+            writeln!(
+                res,
+                "    \"{}\" -> \"{}\" [taillabel=\"{}\", headlabel=\"{}\", {}];",
+                from.0.simple(),
+                to.0.simple(),
+                from.1,
+                to.1,
+                match two_way {
+                    true => "dir=both",
+                    false => "style=dashed",
+                }
+            ).unwrap();
+        }
+
+        writeln!(res, "}}").unwrap();
+        res
+    }
+
+    fn to_json(&self, name: &str) -> String {
+        let mut res = String::new();
+
+        writeln!(res, "{{").unwrap();
+        let traits: Vec<String> = self.player.get_traits().iter()
+            .map(|t| format!("\"{}\"", quoted(t.get_name())))
+            .collect();
+
+        writeln!(
+            res,
+            "  \"player\": {{ \"name\": \"{}\", \"traits\": [{}], \"room\": \"{}\", \"x\": {}, \"y\": {} }},",
+            quoted(name),
+            traits.join(", "),
+            self.player.get_room().simple(),
+            self.player.get_x(),
+            self.player.get_y()
+        ).unwrap();
+
+        let rooms: Vec<String> = self.sorted_rooms().into_iter()
+            .map(|room| {
+                let doors: Vec<String> = doors(&**room).into_iter()
+                    .map(|(idx, (x, y))| format!(
+                        "{{ \"index\": {}, \"x\": {}, \"y\": {}, \"lock\": {} }}",
+                        idx,
+                        x,
+                        y,
+                        self.locks.get(&(room.get_uuid(), idx))
+                            .map_or(String::from("null"), |lock| lock.to_string())
+                    ))
+                    .collect();
+
+                format!(
+                    "    {{ \"id\": \"{}\", \"name\": \"{}\", \"type\": \"{}\", \"light\": \"{}\", \"width\": {}, \"height\": {}, \"doors\": [{}] }}",
+                    room.get_uuid().simple(),
+                    quoted(self.room_name(&room.get_uuid())),
+                    room.get_kind(),
+                    self.lighting(&room.get_uuid()).key(),
+                    room.get_width(),
+                    room.get_height(),
+                    doors.join(", ")
+                )
+            })
+            .collect();
+
+        writeln!(res, "  \"rooms\": [\n{}\n  ],", rooms.join(",\n")).unwrap();
+
+        let links: Vec<String> = self.sorted_links().into_iter()
+            .map(|(&(ref from, from_door), &(ref to, to_door))| format!(
+                "    {{ \"from\": {{ \"room\": \"{}\", \"door\": {} }}, \"to\": {{ \"room\": \"{}\", \"door\": {} }} }}",
+                from.simple(),
+                from_door,
+                to.simple(),
+                to_door
+            ))
+            .collect();
+
+        writeln!(res, "  \"links\": [\n{}\n  ]", links.join(",\n")).unwrap();
+        writeln!(res, "}}").unwrap();
+        res
+    }
+
+    fn to_html(&self, name: &str) -> Result<String, GameError> {
+        let theme = Theme::classic();
+        let mut res = String::new();
+
+        res.push_str(concat!(
+            "<!DOCTYPE html>\n",
+            "<html>\n",
+            "<head>\n",
+            "<meta charset=\"utf-8\">\n",
+            "<title>oaendlig world</title>\n",
+            "<style>\n",
+            "body { background: #111; color: #ccc; font-family: sans-serif; }\n",
+            "section { display: inline-block; vertical-align: top; margin: 1em; }\n",
+            "pre { line-height: 1; }\n",
+            "a { color: #c08040; }\n",
+            ".here { border: 1px solid #ccc; padding: 0 1em; }\n",
+            "</style>\n",
+            "</head>\n",
+            "<body>\n",
+        ));
+
+        write!(res, "<h1>{}", escaped(name)).unwrap();
+        writeln!(res, "'s house: {} rooms, {} doors</h1>", self.rooms.len(), self.links.len()).unwrap();
+
+        for room in self.sorted_rooms() {
+            let uuid = room.get_uuid();
+            let here = uuid == *self.player.get_room();
+
+            writeln!(
+                res,
+                "<section id=\"{}\"{}>",
+                uuid.simple(),
+                match here {
+                    true => " class=\"here\"",
+                    false => "",
+                }
+            ).unwrap();
+            writeln!(
+                res,
+                "<h2>{}</h2>\n<p>{}, {} {}x{}, {}</p>",
+                escaped(self.room_name(&uuid)),
+                short(&uuid),
+                room.get_kind(),
+                room.get_width(),
+                room.get_height(),
+                self.lighting(&uuid).key()
+            ).unwrap();
+
+            //  the room as the classic theme draws it, doors by their index
+            res.push_str("<pre>");
+            for y in 0..room.get_height() {
+                for x in 0..room.get_width() {
+                    let tile = self.room_tile(&uuid, x, y)?;
+                    let ch = match tile.base {
+                        _ if here && (x, y) == (self.player.get_x(), self.player.get_y()) =>
+                            theme.get_base(&Base::Player).ch,
+                        Base::Door(idx) if idx < 10 =>
+                            (b'0' + idx as u8) as char,
+                        ref base => theme.get_base(base).ch,
+                    };
+                    push_escaped(&mut res, ch);
+                }
+                res.push('\n');
+            }
+            res.push_str("</pre>\n<ul>\n");
+
+            for (idx, (x, y)) in doors(&**room) {
+                if let Some(&lock) = self.locks.get(&(uuid, idx)) {
+                    writeln!(res, "<li>door {} takes the {}</li>", idx, Item::key(lock).get_name()).unwrap();
+                }
+
+                match self.links.get(&(uuid, idx)) {
+                    Some(&(ref to, to_door)) => writeln!(
+                        res,
+                        "<li>door {} at {},{} leads to <a href=\"#{}\">{}</a> door {}</li>",
+                        idx, x, y, to.simple(), escaped(self.room_name(to)), to_door
+                    ),
+                    None => writeln!(res, "<li>door {} at {},{} won't open from this side</li>", idx, x, y),
+                }.unwrap();
+            }
+
+            res.push_str("</ul>\n</section>\n");
+        }
+
+        res.push_str("</body>\n</html>\n");
+        Ok(res)
+    }
+}
+
+/// Returns the index and position of every door of given room.
+fn doors(room: &dyn Room) -> Vec<(usize, (usize, usize))> {
+    (0..).map(|idx| room.get_door(idx))
+        .take_while(Option::is_some)
+        .map(Option::unwrap)
+        .enumerate()
+        .collect()
+}
+
+//  the first few digits are enough to tell rooms apart
+fn short(uuid: &Uuid) -> String {
+    uuid.simple().to_string().chars().take(8).collect()
+}
+
+//  given text made safe to go between double quotes in DOT or JSON
+fn quoted(text: &str) -> String {
+    let mut res = String::new();
+    for ch in text.chars() {
+        match ch {
+            '"' | '\\' => { res.push('\\'); res.push(ch); },
+            ch if ch.is_control() => (),
+            ch => res.push(ch),
+        }
+    }
+    res
+}
+
+//  given text made safe to go in HTML
+fn escaped(text: &str) -> String {
+    let mut res = String::new();
+    for ch in text.chars() {
+        push_escaped(&mut res, ch);
+    }
+    res
+}
+
+fn push_escaped(res: &mut String, ch: char) {
+    match ch {
+        '<' => res.push_str("&lt;"),
+        '>' => res.push_str("&gt;"),
+        '&' => res.push_str("&amp;"),
+        ch => res.push(ch),
+    }
+}
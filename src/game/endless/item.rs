@@ -0,0 +1,75 @@
+use tile::Tile;
+
+use utils::rnd_lt;
+
+/// Something that can lie on the floor or be carried around.
+#[derive(Clone)]
+pub struct Item {
+    name: String,
+    glyph: char,
+
+    //  what themes know it by
+    kind: &'static str,
+
+    //  the lock this opens, if it's a key
+    key: Option<usize>,
+}
+
+impl Item {
+    pub fn new(name: &str, glyph: char, kind: &'static str) -> Self {
+        Self {
+            name: String::from(name),
+            glyph: glyph,
+            kind: kind,
+            key: None,
+        }
+    }
+
+    /// The most keys there can be, each with its own look.
+    pub const MAX_KEYS: usize = 6;
+
+    /// Returns the key to the lock with given index.
+    pub fn key(lock: usize) -> Self {
+        let (name, kind) = match lock {
+            0 => ("brass key", "brass-key"),
+            1 => ("iron key", "iron-key"),
+            2 => ("bone key", "bone-key"),
+            3 => ("glass key", "glass-key"),
+            4 => ("rusty key", "rusty-key"),
+            _ => ("green key", "green-key"),
+        };
+
+        Self {
+            key: Some(lock),
+            ..Self::new(name, '-', kind)
+        }
+    }
+
+    /// Returns one of the odds and ends that lie around the house.
+    pub fn rnd() -> Self {
+        let (name, glyph, kind) = match rnd_lt::<u8>(8) {
+            0 => ("candle stub", '!', "candle-stub"),
+            1 => ("smooth pebble", '*', "pebble"),
+            2 => ("torn page", '"', "torn-page"),
+            3 => ("music box", '&', "music-box"),
+            4 => ("compass that points down", '%', "compass"),
+            5 => ("glass eye", '0', "glass-eye"),
+            6 => ("length of red string", '~', "red-string"),
+            _ => ("wilted flower", '$', "wilted-flower"),
+        };
+
+        Self::new(name, glyph, kind)
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_key(&self) -> Option<usize> {
+        self.key
+    }
+
+    pub fn get_tile(&self) -> Tile {
+        Tile::item(self.glyph, self.kind)
+    }
+}
@@ -0,0 +1,127 @@
+//  how well lit each room is. Lit rooms are bright all over, dim ones only
+//  near their lamps and the player's own light, and dark ones swallow every
+//  light there is, so the player has to feel their way round. Brightness goes
+//  from 0 for nothing to `FULL`, and the console darkens colors to match
+
+use tile::base::Base;
+use tile::style::FULL_LIGHT;
+use game::endless::room::Room;
+
+use std::cmp;
+
+use utils::{ rnd, rnd_lt, rnd_within };
+
+/// Brightness of a tile in plain light.
+pub const FULL: u8 = FULL_LIGHT;
+
+/// How bright a tile the player can only feel looks.
+pub const TOUCH: u8 = 2;
+
+/// How far lamps and the light the player carries reach, in tiles.
+const LAMP_RADIUS: usize = 5;
+const PLAYER_RADIUS: usize = 3;
+
+/// How likely a room is to be dim, or dark.
+const DIM_CHANCE: f32 = 0.3;
+const DARK_CHANCE: f32 = 0.1;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Lighting {
+    Lit,
+    Dim,
+    Dark,
+}
+
+impl Lighting {
+    pub fn key(&self) -> &'static str {
+        match self {
+            &Lighting::Lit => "lit",
+            &Lighting::Dim => "dim",
+            &Lighting::Dark => "dark",
+        }
+    }
+
+    /// Returns what the player notices about the light on coming in.
+    pub fn get_description(&self) -> &'static str {
+        match self {
+            &Lighting::Lit => "",
+            &Lighting::Dim => " The light is poor here.",
+            &Lighting::Dark => " It is pitch dark, and you will have to feel your way.",
+        }
+    }
+}
+
+pub struct RoomLight {
+    lighting: Lighting,
+
+    //  lamps hang on the walls, never on doors or in corners
+    lamps: Vec<(usize, usize)>,
+}
+
+impl RoomLight {
+    /// Returns the light of given room: mostly lit, sometimes dim with a lamp
+    /// or three on the walls, now and then dark.
+    pub fn rnd(room: &dyn Room) -> Self {
+        let lighting = match rnd::<f32>() {
+            x if x < DARK_CHANCE => Lighting::Dark,
+            x if x < DARK_CHANCE + DIM_CHANCE => Lighting::Dim,
+            _ => Lighting::Lit,
+        };
+
+        let (w, h) = (room.get_width(), room.get_height());
+        let spots: Vec<(usize, usize)> = (0..h)
+            .flat_map(|y| (0..w).map(move |x| (x, y)))
+            .filter(|&(x, y)| (x == 0 || x == w - 1) != (y == 0 || y == h - 1))
+            .filter(|&(x, y)| room.get_tile(x, y).map_or(false, |tile| tile.base == Base::Wall))
+            .collect();
+
+        let mut lamps = Vec::new();
+        if lighting == Lighting::Dim && !spots.is_empty() {
+            for _ in 0..rnd_within(1..4) {
+                let lamp = spots[rnd_lt(spots.len())];
+                if !lamps.contains(&lamp) {
+                    lamps.push(lamp);
+                }
+            }
+        }
+
+        Self {
+            lighting: lighting,
+            lamps: lamps,
+        }
+    }
+
+    pub fn get_lighting(&self) -> Lighting {
+        self.lighting
+    }
+
+    pub fn has_lamp(&self, x: usize, y: usize) -> bool {
+        self.lamps.contains(&(x, y))
+    }
+
+    /// Returns how bright given tile is, with the player's light at given
+    /// position if they are in the room.
+    pub fn brightness(&self, x: usize, y: usize, player: Option<(usize, usize)>) -> u8 {
+        match self.lighting {
+            Lighting::Lit => FULL,
+            Lighting::Dark => 0,
+            Lighting::Dim => self.lamps.iter()
+                .map(|&lamp| shine(lamp, (x, y), LAMP_RADIUS))
+                .chain(player.map(|player| shine(player, (x, y), PLAYER_RADIUS)))
+                .max()
+                .unwrap_or(0),
+        }
+    }
+}
+
+//  light from given source at given tile, fading out with distance
+fn shine(from: (usize, usize), to: (usize, usize), radius: usize) -> u8 {
+    let dx = (from.0 as isize - to.0 as isize).abs() as usize;
+    let dy = (from.1 as isize - to.1 as isize).abs() as usize;
+    let dist = cmp::max(dx, dy);
+
+    match dist > radius {
+        true => 0,
+        false => (FULL as usize * (radius + 1 - dist) / (radius + 1)) as u8,
+    }
+}
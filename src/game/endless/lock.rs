@@ -0,0 +1,251 @@
+//  locked doors and where their keys go. Keys are never used up, so the player
+//  only gets stuck if they can end up somewhere they can't reach every key
+//  from, e.g. behind a one-way door. Locks are tried one at a time on two-way
+//  links, and any lock that makes that possible is taken out again
+
+use game::endless::RoomDoor;
+
+use uuid::Uuid;
+
+use std::collections::{ HashMap, HashSet, VecDeque };
+
+use utils::rnd_lt;
+
+//  a room and the keys held there, one bit per key
+type State = (Uuid, u32);
+
+struct Plan {
+    //  for each room, its doors and the rooms they lead to, in door order
+    exits: HashMap<Uuid, Vec<(usize, Uuid)>>,
+    locks: HashMap<RoomDoor, usize>,
+
+    //  the room each key lies in
+    keys: Vec<Uuid>,
+}
+
+impl Plan {
+    fn new(links: &HashMap<RoomDoor, RoomDoor>) -> Self {
+        let mut exits: HashMap<Uuid, Vec<(usize, Uuid)>> = HashMap::new();
+        for (&(from, door), &(to, _)) in links.iter() {
+            exits.entry(from).or_insert_with(Vec::new).push((door, to));
+        }
+
+        for doors in exits.values_mut() {
+            doors.sort();
+        }
+
+        Self {
+            exits: exits,
+            locks: HashMap::new(),
+            keys: Vec::new(),
+        }
+    }
+
+    fn pick_up(&self, room: &Uuid, held: u32) -> u32 {
+        self.keys.iter().enumerate()
+            .filter(|&(_, key_room)| key_room == room)
+            .fold(held, |held, (key, _)| held | 1 << key)
+    }
+
+    fn next_states(&self, (room, held): State) -> Vec<State> {
+        self.exits.get(&room).map_or(Vec::new(), |doors| doors.iter()
+            .filter(|&&(door, _)| match self.locks.get(&(room, door)) {
+                Some(&key) => held & 1 << key != 0,
+                None => true,
+            })
+            .map(|&(_, to)| (to, self.pick_up(&to, held)))
+            .collect()
+        )
+    }
+
+    /// Returns every state the player can get to from given room, in the
+    /// order they are found.
+    fn reachable(&self, start: &Uuid) -> Vec<State> {
+        let first = (start.clone(), self.pick_up(start, 0));
+        let mut res = vec![first];
+        let mut seen: HashSet<State> = res.iter().cloned().collect();
+        let mut queue: VecDeque<State> = res.iter().cloned().collect();
+
+        while let Some(state) = queue.pop_front() {
+            for next in self.next_states(state) {
+                if seen.insert(next) {
+                    res.push(next);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        res
+    }
+
+    /// Returns whether the player can get every key from everywhere they can
+    /// get to.
+    fn is_solvable(&self, start: &Uuid) -> bool {
+        let states = self.reachable(start);
+        let all = (1 << self.keys.len()) - 1;
+
+        //  walk backwards from every state that holds all the keys
+        let mut back: HashMap<State, Vec<State>> = HashMap::new();
+        for &state in states.iter() {
+            for next in self.next_states(state) {
+                back.entry(next).or_insert_with(Vec::new).push(state);
+            }
+        }
+
+        let mut good: HashSet<State> = states.iter().cloned()
+            .filter(|&(_, held)| held == all)
+            .collect();
+        let mut queue: VecDeque<State> = good.iter().cloned().collect();
+
+        while let Some(state) = queue.pop_front() {
+            for &prev in back.get(&state).map_or(&[][..], |prevs| &prevs[..]) {
+                if good.insert(prev) {
+                    queue.push_back(prev);
+                }
+            }
+        }
+
+        states.iter().all(|state| good.contains(state))
+    }
+}
+
+/// Picks up to `count` two-way links to lock, and a room for the key to each.
+/// Returns the locked doors, both sides of every link, with the index of
+/// their key, and the room of every key by index.
+pub fn place_locks(
+    links: &HashMap<RoomDoor, RoomDoor>,
+    start: &Uuid,
+    count: usize
+) -> (HashMap<RoomDoor, usize>, Vec<Uuid>) {
+    let mut plan = Plan::new(links);
+
+    let mut candidates: Vec<RoomDoor> = links.iter()
+        .filter(|&(from, to)| from < to && links.get(to) == Some(from))
+        .map(|(from, _)| from.clone())
+        .collect();
+    candidates.sort();
+
+    for _ in 0..count * 4 {
+        if plan.keys.len() == count || candidates.is_empty() {
+            break;
+        }
+
+        let door = candidates.remove(rnd_lt(candidates.len()));
+        let other = links[&door];
+        let key = plan.keys.len();
+
+        //  the key goes somewhere the player can already get to
+        let rooms: Vec<Uuid> = {
+            let mut seen = HashSet::new();
+            plan.reachable(start).into_iter()
+                .map(|(room, _)| room)
+                .filter(|room| seen.insert(*room))
+                .collect()
+        };
+        let key_room = rooms[rnd_lt(rooms.len())];
+
+        plan.locks.insert(door, key);
+        plan.locks.insert(other, key);
+        plan.keys.push(key_room);
+
+        if !plan.is_solvable(start) {
+            plan.locks.remove(&door);
+            plan.locks.remove(&other);
+            plan.keys.pop();
+        }
+    }
+
+    (plan.locks, plan.keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils;
+
+    //  rooms in a row linked both ways, door 1 on to the next and door 0 back
+    fn corridor(len: usize) -> (Vec<Uuid>, HashMap<RoomDoor, RoomDoor>) {
+        let rooms: Vec<Uuid> = (0..len).map(|_| Uuid::new_v4()).collect();
+        let mut links = HashMap::new();
+        for pair in rooms.windows(2) {
+            links.insert((pair[0], 1), (pair[1], 0));
+            links.insert((pair[1], 0), (pair[0], 1));
+        }
+
+        (rooms, links)
+    }
+
+    fn lock(plan: &mut Plan, links: &HashMap<RoomDoor, RoomDoor>, door: RoomDoor, key_room: Uuid) {
+        let key = plan.keys.len();
+        plan.locks.insert(door, key);
+        plan.locks.insert(links[&door], key);
+        plan.keys.push(key_room);
+    }
+
+    #[test]
+    fn key_before_its_lock() {
+        let (rooms, links) = corridor(3);
+        let mut plan = Plan::new(&links);
+        lock(&mut plan, &links, (rooms[0], 1), rooms[0]);
+
+        assert!(plan.is_solvable(&rooms[0]));
+    }
+
+    #[test]
+    fn key_behind_its_own_lock() {
+        let (rooms, links) = corridor(3);
+        let mut plan = Plan::new(&links);
+        lock(&mut plan, &links, (rooms[0], 1), rooms[2]);
+
+        assert!(!plan.is_solvable(&rooms[0]));
+    }
+
+    #[test]
+    fn keys_behind_each_other() {
+        let (rooms, links) = corridor(4);
+        let mut plan = Plan::new(&links);
+        lock(&mut plan, &links, (rooms[0], 1), rooms[0]);
+        lock(&mut plan, &links, (rooms[1], 1), rooms[1]);
+        lock(&mut plan, &links, (rooms[2], 1), rooms[2]);
+
+        assert!(plan.is_solvable(&rooms[0]));
+    }
+
+    #[test]
+    fn one_way_past_a_lock() {
+        //  0 - 1 - 2, with the key to 1 - 2 in 1, and a way from 0 into 2
+        //  that doesn't lead back
+        let (rooms, mut links) = corridor(3);
+        links.insert((rooms[0], 2), (rooms[2], 2));
+
+        let mut plan = Plan::new(&links);
+        lock(&mut plan, &links, (rooms[1], 1), rooms[1]);
+
+        assert!(!plan.is_solvable(&rooms[0]));
+    }
+
+    #[test]
+    fn placed_locks_are_solvable() {
+        utils::seed(7);
+
+        let mut placed = 0;
+        for _ in 0..20 {
+            let (rooms, mut links) = corridor(6);
+            links.insert((rooms[0], 2), (rooms[3], 2));
+            links.insert((rooms[4], 2), (rooms[1], 2));
+
+            let (locks, keys) = place_locks(&links, &rooms[0], 3);
+            for (door, key) in locks.iter() {
+                assert_eq!(locks.get(&links[door]), Some(key));
+            }
+
+            placed += keys.len();
+            let mut plan = Plan::new(&links);
+            plan.locks = locks;
+            plan.keys = keys;
+            assert!(plan.is_solvable(&rooms[0]));
+        }
+
+        assert!(placed > 0);
+    }
+}
@@ -1,36 +1,148 @@
-use console::{ InputType, ArrowType };
+use console::{ InputType, ArrowType, KeyType };
 use tile::Tile;
 use tile::base::Base;
-use game::Game;
+use tile::cover::Cover;
+use game::{ Game, GameError };
+use config::Config;
 use ui::*;
 
 mod room;
 mod player;
+mod npc;
+mod item;
+mod scheduler;
+mod path;
+mod route;
+mod lock;
+mod text;
+mod character;
+mod portal;
+mod camera;
+mod light;
+pub mod export;
 
-use game::endless::room::Room;
-use game::endless::room::square::SquareRoom;
+use game::endless::room::{ Room, entrance, neighbours };
+
+pub use game::endless::room::KINDS as ROOM_KINDS;
+pub use game::endless::item::Item;
+pub use game::endless::camera::CameraMode;
 
 use game::endless::player::Player;
+use game::endless::npc::{ Npc, Behaviour };
+use game::endless::scheduler::{ Action, COARSE_STEPS };
+use game::endless::path::{ find_path, walkable, can_end };
+use game::endless::route::find_route;
+use game::endless::lock::place_locks;
+use game::endless::portal::Portal;
+use game::endless::camera::Camera;
+use game::endless::light::{ RoomLight, Lighting, FULL, TOUCH };
+use game::endless::character::{ Creation, Trait, MAX_NAME, MAX_TRAITS };
 
 use uuid::Uuid;
 use rand::prelude::*;
 
 use std::iter;
-use std::collections::HashMap;
-use std::mem;
+use std::collections::{ HashMap, HashSet, VecDeque };
+use std::cmp;
+use std::time::Duration;
 
-use utils::{ rnd_within, rnd_lt, rnd_string };
+use crash;
+use utils;
+use utils::{ rnd, rnd_within, rnd_lt, repeat_until };
 
 type RoomDoor = (Uuid, usize);
 
+/// How far the player can see, in tiles either way, and how much further a
+/// sharp-eyed one can.
+const SIGHT: usize = 4;
+const SHARP_SIGHT: usize = 2;
+
+/// How many names a room gets to try before it takes a numbered one.
+const NAME_TRIES: usize = 20;
+
+/// How long the player takes for each step while travelling.
+const TRAVEL_STEP: Duration = Duration::from_millis(50);
+
+//  the player walking on their own towards some tile of the current room,
+//  and maybe on through more doors after that
+struct Travel {
+    goal: (usize, usize),
+
+    //  doors to go through once the goal is reached, in order
+    route: Vec<RoomDoor>,
+
+    //  whether to pick a new goal every step, until everything is explored
+    explore: bool,
+
+    //  npcs the player could already see when setting off, which don't
+    //  count as a reason to stop
+    seen: Vec<usize>,
+
+    //  time since the last step
+    wait: Duration,
+}
+
 pub struct EndlessGame {
-    rooms: HashMap<Uuid, Box<Room>>,
+    seed: u64,
+    rooms: HashMap<Uuid, Box<dyn Room>>,
 
-    //  if (A, B) is in links, then (B, A) is in links
+    //  where each door leads. Usually (B, A) is in links if (A, B) is, but
+    //  one-way doors have no way back, and twisted ones lead back elsewhere
     links: HashMap<RoomDoor, RoomDoor>,
     player: Player,
+    npcs: Vec<Npc>,
+
+    //  the character being made, until the run really starts
+    creation: Option<Creation>,
+
+    //  what the player knows each room as
+    names: HashMap<Uuid, String>,
+
+    //  for each room, the door the player last left it through, so followers
+    //  know where to go
+    trail: HashMap<Uuid, usize>,
+
+    //  doors the player knows where they lead, and the rooms they have been
+    //  to in order of first visit
+    known: HashSet<RoomDoor>,
+    visited: Vec<Uuid>,
+
+    //  locked doors, with the index of the key that opens them
+    locks: HashMap<RoomDoor, usize>,
+
+    //  odd doors the player found out about, and how to mark them
+    marks: HashMap<RoomDoor, Cover>,
 
-    //  should be None when buffer is invalid, i.e. doesn't match size
+    //  how each room is lit
+    lights: HashMap<Uuid, RoomLight>,
+
+    //  for each room, the tiles the player has seen
+    explored: HashMap<Uuid, HashSet<(usize, usize)>>,
+
+    //  items lying around, by room and position; the last one is on top
+    floor: HashMap<(Uuid, usize, usize), Vec<Item>>,
+
+    //  the selected line while the inventory is open
+    inventory: Option<usize>,
+
+    //  the selected line while the list of visited rooms is open
+    room_list: Option<usize>,
+
+    //  the visited room shown while the map is open
+    map: Option<usize>,
+
+    //  the tile picked while choosing where to travel to
+    cursor: Option<(usize, usize)>,
+    travel: Option<Travel>,
+
+    //  time steps since the game started
+    time: u64,
+
+    //  which part of the room the map shows
+    camera: Camera,
+
+    //  should be None when buffer is invalid, i.e. doesn't match size or
+    //  anything it shows changed
     buffer: Option<Vec<Tile>>,
     buf_width: usize,
     buf_height: usize,
@@ -39,13 +151,14 @@ pub struct EndlessGame {
 impl EndlessGame {
     fn init(
         &mut self,
+        config: &Config,
         num_rooms: usize,
         num_links: usize,
-    ) {
+    ) -> Result<(), GameError> {
         //  we use these uuids to build rooms later
         let mut uuids: Vec<Uuid> = Vec::new();
         uuids.resize_with(
-            num_rooms as usize, || Uuid::new_v4()
+            num_rooms as usize, || Uuid::from_random_bytes(rnd())
             );
 
         //  the kth element in this vector represents how many doors the kth
@@ -53,108 +166,1368 @@ impl EndlessGame {
         let mut door_cnts: Vec<usize> =
             iter::repeat(0).take(num_rooms).collect();
 
-        //  first build links, then build rooms
+        //  first build links, then build rooms. A random tree of two-way links
+        //  comes first, so that every room can be reached from every other
+        //  one however odd the other doors are
         if num_rooms > 1 {
-            for _ in 0..num_links {
+            let mut order: Vec<usize> = (0..num_rooms).collect();
+            for k in (1..num_rooms).rev() {
+                order.swap(k, rnd_lt(k + 1));
+            }
+
+            //  every link gets new doors at its ends
+            let mut door = |room: usize| {
+                door_cnts[room] += 1;
+                (uuids[room].clone(), door_cnts[room] - 1)
+            };
+
+            for k in 1..num_rooms {
+                let (a, b) = (door(order[k]), door(order[rnd_lt(k)]));
+                self.links.insert(a.clone(), b.clone());
+                self.links.insert(b, a);
+            }
+
+            for _ in (num_rooms - 1)..num_links {
                 //  pick 2 different rooms
-                let room0 = random::<usize>() % num_rooms;
-                let mut room1: usize = room0;
-                while room1 == room0 {
-                    room1 = random::<usize>() % num_rooms;
-                }
+                let room0 = rnd_lt(num_rooms);
+                let room1 = repeat_until(|| rnd_lt(num_rooms), |&room| room != room0);
 
-                let uuid0 = uuids[room0].clone();
-                let uuid1 = uuids[room1].clone();
-                let door0 = door_cnts[room0];
-                let door1 = door_cnts[room1];
+                match rnd::<f32>() {
+                    //  the far side doesn't open, so there is no way back
+                    x if x < config.one_way => {
+                        self.links.insert(door(room0), door(room1));
+                    },
 
-                self.links.insert(
-                    (uuid0, door0), (uuid1, door1)
-                    );
+                    //  three doors that each lead on to the next, so going
+                    //  back ends up somewhere else
+                    x if x < config.one_way + config.twisted && num_rooms > 2 => {
+                        let room2 = repeat_until(
+                            || rnd_lt(num_rooms),
+                            |&room| room != room0 && room != room1
+                        );
 
-                self.links.insert(
-                    (uuid1.clone(), door1), (uuid0.clone(), door0)
-                    );
+                        let (a, b, c) = (door(room0), door(room1), door(room2));
+                        self.links.insert(a.clone(), b.clone());
+                        self.links.insert(b, c.clone());
+                        self.links.insert(c, a);
+                    },
 
-                door_cnts[room0] += 1;
-                door_cnts[room1] += 1;
+                    _ => {
+                        let (a, b) = (door(room0), door(room1));
+                        self.links.insert(a.clone(), b.clone());
+                        self.links.insert(b, a);
+                    },
+                }
             }
         }
 
+        let mut used_names = HashSet::new();
         for x in 0..num_rooms {
             let uuid = uuids[x].clone();
 
-            let room_width = rnd_within::<usize>(10..15);
-            let room_height = rnd_within::<usize>(10..15);
+            let (min_w, max_w) = config.room_width;
+            let (min_h, max_h) = config.room_height;
+            let room_width = rnd_within::<usize>(min_w..max_w + 1);
+            let room_height = rnd_within::<usize>(min_h..max_h + 1);
 
             self.rooms.insert(
                 uuid,
-                Box::new(SquareRoom::new(
+                room::build(
+                    Self::rnd_kind(&config.room_types)?,
                     uuid.clone(),
                     door_cnts[x],
                     room_width,
                     room_height
-                    )
+                )?);
+
+            //  every room gets a name of its own, numbered once the grammar
+            //  keeps coming up with ones already taken
+            let name = (0..NAME_TRIES)
+                .map(|_| text::room_name(room_width, room_height))
+                .find(|name| !used_names.contains(name))
+                .unwrap_or_else(|| {
+                    let name = text::room_name(room_width, room_height);
+                    (2..).map(|n| format!("{} ({})", name, n))
+                        .find(|numbered| !used_names.contains(numbered))
+                        .unwrap()
+                });
+            used_names.insert(name.clone());
+            self.names.insert(uuid, name);
+
+            let light = RoomLight::rnd(&**self.room(&uuid)?);
+            self.lights.insert(uuid, light);
+        }
+
+        let uuid = uuids[rnd::<usize>() % uuids.len()].clone();
+
+        let (x, y) = self.rnd_floor(&uuid)?;
+        self.player = Player::new(uuid, x, y);
+        self.visited.push(uuid);
+        self.look_around()?;
+
+        //  lock a few doors, with every key somewhere the player can get to
+        //  before its lock
+        let (locks, keys) = place_locks(&self.links, &uuid, config.locks);
+        for (lock, room) in keys.into_iter().enumerate() {
+            let (x, y) = self.rnd_floor(&room)?;
+            self.floor.entry((room, x, y))
+                .or_insert_with(Vec::new)
+                .push(Item::key(lock));
+        }
+        self.locks = locks;
+
+        //  a few npcs per room, never on top of the player or each other
+        for uuid in uuids.iter() {
+            for _ in 0..rnd_lt::<usize>(3) {
+                let (x, y) = self.rnd_floor(uuid)?;
+                if !self.is_occupied(uuid, x, y) {
+                    self.npcs.push(Npc::rnd(uuid.clone(), x, y));
+                }
+            }
+
+            for _ in 0..rnd_lt::<usize>(3) {
+                let (x, y) = self.rnd_floor(uuid)?;
+                self.floor.entry((uuid.clone(), x, y))
+                    .or_insert_with(Vec::new)
+                    .push(Item::rnd());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Picks a room kind, each as likely as its weight says.
+    fn rnd_kind(kinds: &[(String, u32)]) -> Result<&str, GameError> {
+        let total: u32 = kinds.iter().map(|&(_, weight)| weight).sum();
+        let mut n = match total {
+            0 => 0,
+            total => rnd_lt(total),
+        };
+
+        for &(ref kind, weight) in kinds.iter() {
+            if n < weight {
+                return Ok(kind);
+            }
+            n -= weight;
+        }
+
+        Err(GameError::Generation(String::from("there are no room types to pick from")))
+    }
+
+    /// Returns the seed this game was made with.
+    pub fn get_seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns every tile of given room that can be stood on.
+    fn floor_tiles(&self, uuid: &Uuid) -> Result<Vec<(usize, usize)>, GameError> {
+        let room = self.room(uuid)?;
+
+        Ok((0..room.get_height())
+            .flat_map(|y| (0..room.get_width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| room.get_tile(x, y).map_or(false, Tile::is_passable))
+            .collect())
+    }
+
+    /// Returns a random tile of given room that can be stood on.
+    fn rnd_floor(&self, uuid: &Uuid) -> Result<(usize, usize), GameError> {
+        let tiles = self.floor_tiles(uuid)?;
+
+        match tiles.is_empty() {
+            true => Err(GameError::Generation(format!("room {} has no floor to stand on", uuid))),
+            false => Ok(tiles[rnd_lt(tiles.len())]),
+        }
+    }
+
+    /// Returns the room with given uuid, which every uuid the game hands
+    /// around should have.
+    fn room(&self, uuid: &Uuid) -> Result<&Box<dyn Room>, GameError> {
+        self.rooms.get(uuid).ok_or_else(||
+            GameError::Corrupt(format!("there is no room {}", uuid))
+        )
+    }
+
+    /// Returns where given door is.
+    fn door_at(&self, door: &RoomDoor) -> Result<(usize, usize), GameError> {
+        self.room(&door.0)?.get_door(door.1).ok_or_else(||
+            GameError::Corrupt(format!("room {} has no door {}", door.0, door.1))
+        )
+    }
+
+    fn here(&self) -> (Uuid, usize, usize) {
+        (self.player.get_room().clone(), self.player.get_x(), self.player.get_y())
+    }
+
+    /// Returns a message about what lies on the player's tile, if anything.
+    fn look_here(&self) -> Option<Ui> {
+        let items = self.floor.get(&self.here())?;
+        let text = match items.len() {
+            0 => return None,
+            1 => format!("You see a {} here.", items[0].get_name()),
+            n => format!("You see a {} and {} more things here.", items[n - 1].get_name(), n - 1),
+        };
+
+        Some(Ui::Message(MessageType::Static, MessagePosition::Bottom, text))
+    }
+
+    fn pick_up(&mut self) -> Result<Option<Ui>, GameError> {
+        let here = self.here();
+        let item = match self.floor.get_mut(&here).and_then(|items| items.pop()) {
+            Some(item) => item,
+            None => return Ok(None),
+        };
+
+        if self.floor.get(&here).map_or(false, |items| items.is_empty()) {
+            self.floor.remove(&here);
+        }
+
+        let text = format!("You pick up the {}.", item.get_name());
+        self.player.add_item(item);
+        self.pass_time(Action::Step)?;
+
+        Ok(Some(Ui::Message(MessageType::Static, MessagePosition::Bottom, text)))
+    }
+
+    fn drop(&mut self, index: usize) -> Result<Option<Ui>, GameError> {
+        //  keys stay with the player, so they can't be lost behind a door
+        if let Some(item) = self.player.get_inventory().get(index).filter(|item| item.get_key().is_some()) {
+            let text = format!("You'd rather keep the {} close.", item.get_name());
+            return Ok(Some(Ui::Message(MessageType::Static, MessagePosition::Bottom, text)));
+        }
+
+        let item = match self.player.remove_item(index) {
+            Some(item) => item,
+            None => return Ok(None),
+        };
+        let text = format!("You drop the {}.", item.get_name());
+
+        self.floor.entry(self.here())
+            .or_insert_with(Vec::new)
+            .push(item);
+        self.pass_time(Action::Step)?;
+
+        Ok(Some(Ui::Message(MessageType::Static, MessagePosition::Bottom, text)))
+    }
+
+    fn inventory_panel(&self) -> Panel {
+        let items = self.player.get_inventory();
+
+        Panel {
+            title: String::from("You are carrying"),
+            lines: match items.is_empty() {
+                true => vec![String::from("nothing at all")],
+                false => items.iter().map(|item| String::from(item.get_name())).collect(),
+            },
+            selected: match items.is_empty() {
+                true => None,
+                false => self.inventory,
+            },
+            footer: String::from("up/down: select   d: drop   i: close"),
+        }
+    }
+
+    fn react_inventory(&mut self, input: InputType) -> Result<Vec<Ui>, GameError> {
+        let mut res = Vec::new();
+        let len = self.player.get_inventory().len();
+        let sel = self.inventory.unwrap_or(0);
+
+        match input {
+            InputType::Arrow(ArrowType::Up) if sel > 0 => {
+                self.inventory = Some(sel - 1);
+            },
+
+            InputType::Arrow(ArrowType::Down) if sel + 1 < len => {
+                self.inventory = Some(sel + 1);
+            },
+
+            InputType::Char('d') => {
+                res.extend(self.drop(sel)?);
+                if sel > 0 && sel + 1 >= len {
+                    self.inventory = Some(sel - 1);
+                }
+            },
+
+            InputType::Char('i') | InputType::Char('q') => {
+                self.inventory = None;
+                res.push(Ui::Panel(None));
+                return Ok(res);
+            },
+
+            _ => (),
+        }
+
+        res.push(Ui::Panel(Some(self.inventory_panel())));
+        Ok(res)
+    }
+
+    /// Returns whether the player or an npc stands on given tile.
+    fn is_occupied(&self, room: &Uuid, x: usize, y: usize) -> bool {
+        let player = &self.player;
+        if player.get_room() == room && player.get_x() == x && player.get_y() == y {
+            return true;
+        }
+
+        self.npcs.iter().any(|npc|
+            npc.get_room() == room && npc.get_x() == x && npc.get_y() == y
+        )
+    }
+
+    /// Lets time pass after the player spent energy on given action, until
+    /// the player may act again. Npcs in the player's room act as soon as
+    /// they can; everyone else only catches up every `COARSE_STEPS` steps.
+    fn pass_time(&mut self, action: Action) -> Result<(), GameError> {
+        self.player.get_energy_mut().spend(action);
+
+        while !self.player.get_energy().can_act() {
+            self.time += 1;
+            self.player.get_energy_mut().gain();
+
+            let coarse = self.time % COARSE_STEPS == 0;
+
+            for i in 0..self.npcs.len() {
+                self.npcs[i].get_energy_mut().gain();
+
+                let nearby = self.npcs[i].get_room() == self.player.get_room();
+                if !nearby && !coarse {
+                    continue;
+                }
+
+                while self.npcs[i].get_energy_mut().can_act() {
+                    let action = self.npc_act(i)?;
+                    self.npcs[i].get_energy_mut().spend(action);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Lets the npc with given index do one thing, and returns what it did.
+    fn npc_act(&mut self, i: usize) -> Result<Action, GameError> {
+        let room_id = self.npcs[i].get_room().clone();
+        let room = self.room(&room_id)?;
+
+        //  followers chase the player around, or else go where the player
+        //  went; fleers only care while the player is around
+        let same_room = *self.player.get_room() == room_id;
+        let player_pos = (self.player.get_x(), self.player.get_y());
+        let target = match (self.npcs[i].get_behaviour(), same_room) {
+            (Behaviour::Wander, _) => None,
+            (_, true) => Some(player_pos),
+            (Behaviour::Follow, false) => self.trail.get(&room_id)
+                .and_then(|&door| room.get_door(door)),
+            (Behaviour::Flee, false) => None,
+        };
+
+        let step = self.npcs[i].choose_step(
+            &**room,
+            target,
+            |x, y| !self.is_occupied(&room_id, x, y)
+        );
+
+        let (nx, ny) = match step {
+            Some(step) => step,
+            None => return Ok(Action::Wait),
+        };
+
+        match room.get_tile(nx, ny).map(|tile| &tile.base) {
+            Some(&Base::Door(idx)) => {
+                let (to_room, to_door) = match self.links.get(&(room_id, idx)) {
+                    Some(&to) if !self.locks.contains_key(&(room_id, idx)) => to,
+                    _ => return Ok(Action::Wait),
+                };
+
+                let to = entrance(&**self.room(&to_room)?, to_door);
+                if let Some((tx, ty)) = to {
+                    if !self.is_occupied(&to_room, tx, ty) {
+                        self.npcs[i].set_room(to_room);
+                        self.npcs[i].set_x(tx);
+                        self.npcs[i].set_y(ty);
+                        return Ok(Action::Door);
+                    }
+                }
+
+                Ok(Action::Wait)
+            },
+
+            _ => {
+                self.npcs[i].set_x(nx);
+                self.npcs[i].set_y(ny);
+                Ok(Action::Step)
+            },
+        }
+    }
+
+    /// Moves the player onto given tile of the current room, through the
+    /// door if it is one. Returns None if the player can't go there.
+    fn move_player(&mut self, nx: usize, ny: usize) -> Result<Option<Vec<Ui>>, GameError> {
+        let mut res = Vec::new();
+
+        self.camera.moved(
+            nx as i32 - self.player.get_x() as i32,
+            ny as i32 - self.player.get_y() as i32
+        );
+
+        let blocked = self.is_occupied(self.player.get_room(), nx, ny);
+        let base = self.room(self.player.get_room())?
+            .get_tile(nx, ny)
+            .map(|tile| tile.base.clone());
+
+        match base {
+            _ if blocked => { return Ok(None); },
+
+            Some(Base::Ground) => {
+                self.player.set_x(nx);
+                self.player.set_y(ny);
+                self.look_around()?;
+                self.pass_time(Action::Step)?;
+                res.extend(self.look_here());
+            },
+
+            Some(Base::Door(idx)) => {
+                let from = (self.player.get_room().clone(), idx);
+
+                if let Some(&lock) = self.locks.get(&from) {
+                    let key = Item::key(lock);
+                    let text = match self.has_key(lock) {
+                        true => {
+                            //  both sides of the door open
+                            self.locks.retain(|_, &mut other| other != lock);
+                            self.pass_time(Action::Step)?;
+                            format!("You unlock the door with the {}.", key.get_name())
+                        },
+                        false => format!("The door is locked. It takes the {}.", key.get_name()),
+                    };
+
+                    res.push(Ui::Message(MessageType::Static, MessagePosition::Bottom, text));
+                    return Ok(Some(res));
+                }
+
+                let (to_room, to_door) = match self.links.get(&from) {
+                    Some(&to) => to,
+                    None => {
+                        self.marks.insert(from, Cover::Shut);
+                        res.push(Ui::Message(
+                            MessageType::Static,
+                            MessagePosition::Bottom,
+                            String::from("The door won't open from this side.")
+                        ));
+                        return Ok(Some(res));
+                    },
+                };
+
+                //  land somewhere random, but not on an npc; with nowhere left
+                //  to land, the door doesn't give, and whoever travels stops
+                let free: Vec<(usize, usize)> = self.floor_tiles(&to_room)?.into_iter()
+                    .filter(|&(x, y)| !self.is_occupied(&to_room, x, y))
+                    .collect();
+                if free.is_empty() {
+                    self.travel = None;
+                    res.push(Ui::Message(
+                        MessageType::Static,
+                        MessagePosition::Bottom,
+                        String::from("The door won't give. Something is in the way on the other side.")
+                    ));
+                    return Ok(Some(res));
+                }
+                let (tx, ty) = free[rnd_lt(free.len())];
+
+                //  the player can tell when a door doesn't lead back
+                self.known.insert(from.clone());
+                let note = match self.links.get(&(to_room, to_door)) {
+                    Some(back) if *back == from => {
+                        self.known.insert((to_room, to_door));
+                        None
+                    },
+                    Some(_) => {
+                        self.marks.insert(from, Cover::Twisted);
+                        Some("The door twists shut behind you. It won't lead back there.")
+                    },
+                    None => {
+                        self.marks.insert(from, Cover::OneWay);
+                        Some("The door clicks shut behind you. There is no handle on this side.")
+                    },
+                };
+                if !self.visited.contains(&to_room) {
+                    self.visited.push(to_room);
+                }
+
+                self.trail.insert(self.player.get_room().clone(), idx);
+                self.player.set_room(to_room.clone());
+                self.player.set_x(tx);
+                self.player.set_y(ty);
+                self.look_around()?;
+                self.pass_time(Action::Door)?;
+
+                let text = match note {
+                    Some(note) => format!("{} {}", note, self.enter_message()?),
+                    None => self.enter_message()?,
+                };
+                res.push(Ui::Message(MessageType::Static, MessagePosition::Bottom, text));
+            },
+
+            _ => { return Ok(None); },
+        }
+
+        Ok(Some(res))
+    }
+
+    /// Returns how far the player can see.
+    fn sight(&self) -> usize {
+        match self.player.has_trait(Trait::SharpEyed) {
+            true => SIGHT + SHARP_SIGHT,
+            false => SIGHT,
+        }
+    }
+
+    fn lighting(&self, uuid: &Uuid) -> Lighting {
+        self.lights.get(uuid).map_or(Lighting::Lit, RoomLight::get_lighting)
+    }
+
+    /// Returns how bright given tile of given room is, counting the light the
+    /// player carries if they are there.
+    fn brightness(&self, uuid: &Uuid, x: usize, y: usize) -> u8 {
+        let player = match uuid == self.player.get_room() {
+            true => Some((self.player.get_x(), self.player.get_y())),
+            false => None,
+        };
+
+        self.lights.get(uuid).map_or(FULL, |light| light.brightness(x, y, player))
+    }
+
+    /// Returns whether given position, relative to the player's room and
+    /// maybe beyond it, is within sight, or close enough to touch if the
+    /// room is dark. Says nothing about whether there is light to see by.
+    fn in_reach(&self, x: i32, y: i32) -> bool {
+        let dx = (x - self.player.get_x() as i32).abs() as usize;
+        let dy = (y - self.player.get_y() as i32).abs() as usize;
+
+        match self.lighting(self.player.get_room()) {
+            Lighting::Dark => dx + dy <= 1,
+            _ => dx <= self.sight() && dy <= self.sight(),
+        }
+    }
+
+    /// Returns whether given tile of the player's room is within sight, and
+    /// lit enough to see, or else close enough to touch.
+    fn in_view(&self, x: usize, y: usize) -> bool {
+        self.in_reach(x as i32, y as i32) && match self.lighting(self.player.get_room()) {
+            Lighting::Dark => true,
+            _ => self.brightness(self.player.get_room(), x, y) > 0,
+        }
+    }
+
+    /// Marks the tiles the player can see as seen.
+    fn look_around(&mut self) -> Result<(), GameError> {
+        let (w, h) = {
+            let room = self.room(self.player.get_room())?;
+            (room.get_width(), room.get_height())
+        };
+
+        let (px, py) = (self.player.get_x(), self.player.get_y());
+        let sight = self.sight();
+
+        let seen: Vec<(usize, usize)> = (py.saturating_sub(sight)..cmp::min(py + sight + 1, h))
+            .flat_map(|y| (px.saturating_sub(sight)..cmp::min(px + sight + 1, w)).map(move |x| (x, y)))
+            .filter(|&(x, y)| self.in_view(x, y))
+            .collect();
+
+        self.explored.entry(self.player.get_room().clone())
+            .or_insert_with(HashSet::new)
+            .extend(seen);
+
+        Ok(())
+    }
+
+    /// Returns whether the player has seen given tile of given room.
+    fn is_explored(&self, uuid: &Uuid, x: usize, y: usize) -> bool {
+        self.explored.get(uuid).map_or(false, |explored| explored.contains(&(x, y)))
+    }
+
+    /// Returns given tile of given room, with its lamps, and locked doors and
+    /// the odd ones the player knows about marked.
+    fn room_tile(&self, uuid: &Uuid, x: usize, y: usize) -> Result<Tile, GameError> {
+        let mut tile = self.room(uuid)?.get_tile(x, y)
+            .ok_or_else(|| GameError::Corrupt(format!("room {} has no tile at {}, {}", uuid, x, y)))?
+            .clone()
+            .at(x, y);
+        if self.lights.get(uuid).map_or(false, |light| light.has_lamp(x, y)) {
+            return Ok(Tile::lamp().at(x, y));
+        }
+
+        if let Base::Door(idx) = tile.base {
+            let door = (uuid.clone(), idx);
+            tile.cover = match self.locks.contains_key(&door) {
+                true => Some(Cover::Locked),
+                false => self.marks.get(&door).cloned(),
+            };
+        }
+
+        Ok(tile)
+    }
+
+    /// Returns how the item on top at given spot looks, if there is one.
+    fn top_item(&self, spot: &(Uuid, usize, usize)) -> Option<Tile> {
+        self.floor.get(spot)?.last().map(Item::get_tile)
+    }
+
+    /// Returns the indices of the npcs the player can see.
+    fn npcs_in_view(&self) -> Vec<usize> {
+        (0..self.npcs.len())
+            .filter(|&i| self.npcs[i].get_room() == self.player.get_room())
+            .filter(|&i| self.in_view(self.npcs[i].get_x(), self.npcs[i].get_y()))
+            .collect()
+    }
+
+    /// Returns whether something turned up that should stop the player from
+    /// walking on automatically, i.e. an npc that wasn't in view before.
+    fn is_distracted(&self, seen: &[usize]) -> bool {
+        self.npcs_in_view().into_iter().any(|i| !seen.contains(&i))
+    }
+
+    fn react_cursor(&mut self, input: InputType) -> Result<Vec<Ui>, GameError> {
+        let mut res = Vec::new();
+        let (cx, cy) = self.cursor.unwrap();
+        let (w, h) = {
+            let room = self.room(self.player.get_room())?;
+            (room.get_width(), room.get_height())
+        };
+
+        match input {
+            InputType::Arrow(ArrowType::Left) if cx > 0 => { self.cursor = Some((cx - 1, cy)); },
+            InputType::Arrow(ArrowType::Right) if cx + 1 < w => { self.cursor = Some((cx + 1, cy)); },
+            InputType::Arrow(ArrowType::Up) if cy > 0 => { self.cursor = Some((cx, cy - 1)); },
+            InputType::Arrow(ArrowType::Down) if cy + 1 < h => { self.cursor = Some((cx, cy + 1)); },
+
+            //  jump to the next door
+            InputType::Char('\t') => {
+                let room = self.room(self.player.get_room())?;
+                let doors: Vec<(usize, usize)> = (0..)
+                    .map(|idx| room.get_door(idx))
+                    .take_while(Option::is_some)
+                    .map(Option::unwrap)
+                    .collect();
+
+                let next = doors.iter()
+                    .position(|&d| d == (cx, cy))
+                    .map_or(0, |i| (i + 1) % doors.len().max(1));
+                self.cursor = doors.get(next).cloned().or(self.cursor);
+            },
+
+            InputType::Key(KeyType::Enter) | InputType::Char('\n') | InputType::Char('\r') => {
+                self.cursor = None;
+                res.extend(self.start_travel((cx, cy), Vec::new())?);
+            },
+
+            InputType::Char('t') | InputType::Char('q') => {
+                self.cursor = None;
+                res.push(Ui::Message(
+                    MessageType::Static,
+                    MessagePosition::Bottom,
+                    String::from("Never mind.")
                 ));
+            },
+
+            _ => (),
+        }
+
+        Ok(res)
+    }
+
+    fn start_travel(&mut self, goal: (usize, usize), route: Vec<RoomDoor>) -> Result<Option<Ui>, GameError> {
+        let here = (self.player.get_x(), self.player.get_y());
+        let path = {
+            let room = self.room(self.player.get_room())?;
+            find_path(&**room, here, goal, walkable)
+        };
+
+        let text = match path {
+            None => "You can't see a way there.",
+            Some(ref path) if path.is_empty() => "You are already there.",
+            Some(_) => {
+                self.travel = Some(Travel {
+                    goal: goal,
+                    route: route,
+                    explore: false,
+                    seen: self.npcs_in_view(),
+                    wait: Duration::from_millis(0),
+                });
+                return Ok(None);
+            },
+        };
+
+        Ok(Some(Ui::Message(MessageType::Static, MessagePosition::Bottom, String::from(text))))
+    }
+
+    /// Sets off towards the room with given uuid, through doors the player
+    /// already knows.
+    fn travel_to_room(&mut self, to: &Uuid) -> Result<Option<Ui>, GameError> {
+        let route = find_route(&self.rooms, &self.links, &self.known, self.here(), to);
+
+        let text = match route {
+            None => "You don't remember the way there.",
+            Some(ref route) if route.is_empty() => "You are already there.",
+            Some(mut route) => {
+                let door = self.door_at(&route.remove(0))?;
+                return self.start_travel(door, route);
+            },
+        };
+
+        Ok(Some(Ui::Message(MessageType::Static, MessagePosition::Bottom, String::from(text))))
+    }
+
+    fn stop_travel(&mut self, text: &str) -> Ui {
+        self.travel = None;
+        Ui::Message(MessageType::Static, MessagePosition::Bottom, String::from(text))
+    }
+
+    /// Takes the next step towards the travel goal, and goes on to the next
+    /// door of the route on arriving in another room.
+    fn travel_step(&mut self) -> Result<Vec<Ui>, GameError> {
+        let room = self.player.get_room().clone();
+        let goal = match self.travel {
+            Some(Travel { explore: true, .. }) => match self.explore_goal()? {
+                Some((goal, route)) => {
+                    let travel = self.travel.as_mut().unwrap();
+                    travel.goal = goal;
+                    travel.route = route;
+                    goal
+                },
+                None => {
+                    return Ok(vec![self.stop_travel("There is nothing left to explore.")]);
+                },
+            },
+            Some(ref travel) => travel.goal,
+            None => return Ok(Vec::new()),
+        };
+
+        let here = (self.player.get_x(), self.player.get_y());
+        let next = {
+            let room = self.room(&room)?;
+            find_path(&**room, here, goal, |x, y, tile|
+                walkable(x, y, tile) && !self.is_occupied(&room.get_uuid(), x, y)
+            ).and_then(|path| path.first().cloned())
+        };
+
+        let moved = match next {
+            Some((nx, ny)) => self.move_player(nx, ny)?,
+            None => None,
+        };
+
+        let mut res = match moved {
+            Some(res) => res,
+            None => {
+                return Ok(vec![self.stop_travel("Something is in the way.")]);
+            },
+        };
+
+        //  a door that wouldn't give stopped the travel
+        if self.travel.is_none() {
+            return Ok(res);
+        }
+
+        //  through a door, on to the next one if there is one; explorers pick
+        //  a new goal next step anyway
+        if *self.player.get_room() != room {
+            let explore = self.travel.as_ref().unwrap().explore;
+            let next = match self.travel.as_ref().unwrap().route.first() {
+                _ if explore => Some(goal),
+                Some(door) if door.0 == *self.player.get_room() =>
+                    Some(self.door_at(door)?),
+                _ => None,
+            };
+
+            let seen = self.npcs_in_view();
+
+            match next {
+                _ if !seen.is_empty() => {
+                    res.push(self.stop_travel("Something catches your eye, and you stop."));
+                },
+                Some(door) => {
+                    let travel = self.travel.as_mut().unwrap();
+                    if !explore {
+                        travel.route.remove(0);
+                    }
+                    travel.goal = door;
+                    travel.seen = seen;
+                },
+                None => { self.travel = None; },
+            }
+
+            return Ok(res);
+        }
+
+        //  a door that won't open is as far as the player gets, unless
+        //  exploring, which just picks another
+        let stuck = (self.player.get_x(), self.player.get_y()) == here;
+        let arrived = (self.player.get_x(), self.player.get_y()) == goal || stuck;
+        let (distracted, explore) = {
+            let travel = self.travel.as_ref().unwrap();
+            (self.is_distracted(&travel.seen), travel.explore)
+        };
+
+        if arrived && !explore {
+            self.travel = None;
+        } else if distracted {
+            res.push(self.stop_travel("Something catches your eye, and you stop."));
         }
 
-        let uuid = uuids[random::<usize>() % uuids.len()].clone();
+        Ok(res)
+    }
+
+    fn has_key(&self, lock: usize) -> bool {
+        self.player.get_inventory().iter().any(|item| item.get_key() == Some(lock))
+    }
+
+    /// Returns whether there's nothing left to find out at given door: the
+    /// player knows where it leads, or that it won't open, or it's locked and
+    /// they don't have the key.
+    fn is_door_done(&self, door: &RoomDoor) -> bool {
+        let locked = self.locks.get(door).map_or(false, |&lock| !self.has_key(lock));
+        self.known.contains(door) || self.marks.contains_key(door) || locked
+    }
+
+    /// Returns where to go next to explore: the nearest tile of the room not
+    /// seen yet, else the nearest door not used yet, else the first door on
+    /// the way to the nearest room that has one, and the rest of that way.
+    fn explore_goal(&self) -> Result<Option<((usize, usize), Vec<RoomDoor>)>, GameError> {
+        let uuid = self.player.get_room();
+        let room = self.room(uuid)?;
+        let explored = self.explored.get(uuid);
+        let is_seen = |x, y| explored.map_or(false, |explored| explored.contains(&(x, y)));
+
+        //  breadth first over free tiles, so whatever turns up first is nearest
+        let start = (self.player.get_x(), self.player.get_y());
+        let mut queue = VecDeque::new();
+        let mut visited = HashSet::new();
+        let mut unused_door = None;
+
+        queue.push_back(start);
+        visited.insert(start);
 
-        self.player = Player::new(
-            uuid,
-            rnd_lt(self.rooms.get(&uuid).unwrap().get_width() - 2) + 1,
-            rnd_lt(self.rooms.get(&uuid).unwrap().get_height() - 2) + 1
+        while let Some((x, y)) = queue.pop_front() {
+            for (nx, ny) in neighbours(x, y) {
+                let tile = match room.get_tile(nx, ny) {
+                    Some(tile) if visited.insert((nx, ny)) => tile,
+                    _ => continue,
+                };
+
+                //  walls only get seen on the way, there's no going to them
+                if !is_seen(nx, ny) && can_end(tile) {
+                    return Ok(Some(((nx, ny), Vec::new())));
+                }
+
+                match tile.base {
+                    Base::Door(idx) if !self.is_door_done(&(uuid.clone(), idx)) => {
+                        unused_door = unused_door.or(Some((nx, ny)));
+                    },
+                    _ if walkable(nx, ny, tile) && !self.is_occupied(uuid, nx, ny) => {
+                        queue.push_back((nx, ny));
+                    },
+                    _ => (),
+                }
+            }
+        }
+
+        if let Some(door) = unused_door {
+            return Ok(Some((door, Vec::new())));
+        }
+
+        //  nothing left here, so head for the nearest room with unused doors
+        let unfinished = |uuid: &Uuid| self.rooms.get(uuid).map_or(false, |room|
+            (0..).map(|idx| room.get_door(idx))
+                .take_while(Option::is_some)
+                .enumerate()
+                .any(|(idx, _)| !self.is_door_done(&(uuid.clone(), idx)))
         );
+
+        let route = self.visited.iter()
+            .filter(|&other| other != uuid && unfinished(other))
+            .filter_map(|other|
+                find_route(&self.rooms, &self.links, &self.known, self.here(), other)
+            )
+            .filter(|route| !route.is_empty())
+            .min_by_key(Vec::len);
+
+        match route {
+            Some(mut route) => {
+                let door = self.door_at(&route.remove(0))?;
+                Ok(Some((door, route)))
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn start_explore(&mut self) -> Result<Option<Ui>, GameError> {
+        match self.explore_goal()? {
+            Some((goal, route)) => {
+                self.travel = Some(Travel {
+                    goal: goal,
+                    route: route,
+                    explore: true,
+                    seen: self.npcs_in_view(),
+                    wait: Duration::from_millis(0),
+                });
+                Ok(None)
+            },
+            None => Ok(Some(Ui::Message(
+                MessageType::Static,
+                MessagePosition::Bottom,
+                String::from("There is nothing left to explore.")
+            ))),
+        }
+    }
+
+    fn room_list_panel(&self) -> Panel {
+        Panel {
+            title: String::from("Rooms you have been to"),
+            lines: self.visited.iter()
+                .map(|uuid| match uuid == self.player.get_room() {
+                    true => format!("{} (you are here)", self.room_name(uuid)),
+                    false => String::from(self.room_name(uuid)),
+                })
+                .collect(),
+            selected: self.room_list,
+            footer: String::from("up/down: select   enter: go there   r: close"),
+        }
+    }
+
+    fn react_room_list(&mut self, input: InputType) -> Result<Vec<Ui>, GameError> {
+        let mut res = Vec::new();
+        let len = self.visited.len();
+        let sel = self.room_list.unwrap_or(0);
+
+        match input {
+            InputType::Arrow(ArrowType::Up) if sel > 0 => {
+                self.room_list = Some(sel - 1);
+            },
+
+            InputType::Arrow(ArrowType::Down) if sel + 1 < len => {
+                self.room_list = Some(sel + 1);
+            },
+
+            InputType::Key(KeyType::Enter) | InputType::Char('\n') | InputType::Char('\r') => {
+                let to = self.visited[sel];
+                self.room_list = None;
+                res.push(Ui::Panel(None));
+                res.extend(self.travel_to_room(&to)?);
+                return Ok(res);
+            },
+
+            InputType::Char('r') | InputType::Char('q') => {
+                self.room_list = None;
+                res.push(Ui::Panel(None));
+                return Ok(res);
+            },
+
+            _ => (),
+        }
+
+        res.push(Ui::Panel(Some(self.room_list_panel())));
+        Ok(res)
+    }
+
+    fn map_message(&self) -> Result<Ui, GameError> {
+        let uuid = self.visited[self.map.unwrap_or(0)];
+        let room = self.room(&uuid)?;
+        let seen = self.explored.get(&uuid).map_or(0, HashSet::len);
+
+        let here = match &uuid == self.player.get_room() {
+            true => " (you are here)",
+            false => "",
+        };
+
+        Ok(Ui::Message(
+            MessageType::Static,
+            MessagePosition::Bottom,
+            format!(
+                "Map of {}{}, {} of {} tiles seen. left/right: other rooms   enter: go there   m: close",
+                self.room_name(&uuid),
+                here,
+                seen,
+                room.get_width() * room.get_height()
+            )
+        ))
     }
 
-    fn mysterious_message() -> &'static str {
-        match rnd_lt::<u8>(5) {
-            0 => "Message number 0.",
-            1 => "Message number 1.",
-            2 => "Message number 2.",
-            3 => "Message number 3.",
-            _ => "Message number 4.",
+    fn react_map(&mut self, input: InputType) -> Result<Vec<Ui>, GameError> {
+        let mut res = Vec::new();
+        let len = self.visited.len();
+        let sel = self.map.unwrap_or(0);
+
+        match input {
+            InputType::Arrow(ArrowType::Left) | InputType::Arrow(ArrowType::Up) => {
+                self.map = Some((sel + len - 1) % len);
+            },
+
+            InputType::Arrow(ArrowType::Right) | InputType::Arrow(ArrowType::Down) => {
+                self.map = Some((sel + 1) % len);
+            },
+
+            InputType::Key(KeyType::Enter) | InputType::Char('\n') | InputType::Char('\r') => {
+                let to = self.visited[sel];
+                self.map = None;
+                res.extend(self.travel_to_room(&to)?);
+                return Ok(res);
+            },
+
+            InputType::Char('m') | InputType::Char('q') => {
+                self.map = None;
+                res.push(Ui::Message(
+                    MessageType::Static,
+                    MessagePosition::Bottom,
+                    self.enter_message()?
+                ));
+                return Ok(res);
+            },
+
+            _ => (),
         }
+
+        res.push(self.map_message()?);
+        Ok(res)
+    }
+
+    /// Returns what the map shows of the room picked on it: everything the
+    /// player remembers of it, in the middle of the screen.
+    fn map_buffer(&self, sel: usize) -> Result<Vec<Tile>, GameError> {
+        let uuid = &self.visited[sel];
+        let (bw, bh) = (self.buf_width as i32, self.buf_height as i32);
+        let (rw, rh) = {
+            let room = self.room(uuid)?;
+            (room.get_width() as i32, room.get_height() as i32)
+        };
+        let (sx, sy) = ((rw - bw) / 2, (rh - bh) / 2);
+        let is_here = uuid == self.player.get_room();
+
+        let mut tiles = Vec::new();
+        for y in sy..sy + bh {
+            for x in sx..sx + bw {
+                let (ux, uy) = (x as usize, y as usize);
+                let spot = (uuid.clone(), ux, uy);
+
+                let tile = match x >= 0 && y >= 0 && self.is_explored(uuid, ux, uy) {
+                    false => Tile::void(),
+                    true if is_here && (ux, uy) == (self.player.get_x(), self.player.get_y()) =>
+                        Tile::player(),
+                    true => match self.top_item(&spot) {
+                        Some(tile) => tile,
+                        None => self.room_tile(uuid, ux, uy)?,
+                    },
+                };
+
+                tiles.push(tile);
+            }
+        }
+
+        Ok(tiles)
+    }
+
+    fn name_prompt(&self) -> Ui {
+        let name = self.creation.as_ref().map_or("", |creation| creation.name.as_str());
+
+        Ui::Message(
+            MessageType::Prompt,
+            MessagePosition::Bottom,
+            format!("What is your name? {}", name)
+        )
+    }
+
+    fn traits_panel(&self) -> Panel {
+        let creation = self.creation.as_ref().unwrap();
+
+        Panel {
+            title: format!("Who is {}? Pick up to {}", creation.name, MAX_TRAITS),
+            lines: Trait::ALL.iter()
+                .map(|t| format!(
+                    "[{}] {}: {}",
+                    match creation.traits.contains(t) {
+                        true => 'x',
+                        false => ' ',
+                    },
+                    t.get_name(),
+                    t.get_description()
+                ))
+                .collect(),
+            selected: creation.selected,
+            footer: String::from("up/down: select   space: pick   enter: begin"),
+        }
+    }
+
+    fn react_creation(&mut self, input: InputType) -> Result<Vec<Ui>, GameError> {
+        let mut res = Vec::new();
+
+        //  first the name, one key at a time
+        if self.creation.as_ref().unwrap().selected.is_none() {
+            let creation = self.creation.as_mut().unwrap();
+
+            match input {
+                InputType::Key(KeyType::Enter) |
+                InputType::Char('\n') |
+                InputType::Char('\r') if !creation.name.trim().is_empty() => {
+                    creation.name = String::from(creation.name.trim());
+                    creation.selected = Some(0);
+                },
+
+                InputType::Key(KeyType::Backspace) |
+                InputType::Char('\u{7f}') |
+                InputType::Char('\u{8}') => {
+                    creation.name.pop();
+                },
+
+                InputType::Char(ch) if !ch.is_control() && creation.name.chars().count() < MAX_NAME => {
+                    creation.name.push(ch);
+                },
+
+                _ => (),
+            }
+
+            match creation.selected {
+                Some(_) => (),
+                None => {
+                    res.push(self.name_prompt());
+                    return Ok(res);
+                },
+            }
+
+            res.push(Ui::Clear);
+            res.push(Ui::Panel(Some(self.traits_panel())));
+            return Ok(res);
+        }
+
+        let sel = self.creation.as_ref().unwrap().selected.unwrap();
+
+        match input {
+            InputType::Arrow(ArrowType::Up) if sel > 0 => {
+                self.creation.as_mut().unwrap().selected = Some(sel - 1);
+            },
+
+            InputType::Arrow(ArrowType::Down) if sel + 1 < Trait::ALL.len() => {
+                self.creation.as_mut().unwrap().selected = Some(sel + 1);
+            },
+
+            InputType::Char(' ') => {
+                self.creation.as_mut().unwrap().toggle(Trait::ALL[sel]);
+            },
+
+            InputType::Key(KeyType::Enter) |
+            InputType::Char('\n') |
+            InputType::Char('\r') => {
+                res.push(Ui::Panel(None));
+                res.push(self.finish_creation()?);
+                return Ok(res);
+            },
+
+            _ => (),
+        }
+
+        res.push(Ui::Panel(Some(self.traits_panel())));
+        Ok(res)
+    }
+
+    /// Makes the player who they said they are, and starts the run proper.
+    fn finish_creation(&mut self) -> Result<Ui, GameError> {
+        let creation = self.creation.take().unwrap();
+        self.player.set_name(&creation.name);
+        self.player.set_traits(&creation.traits);
+
+        if self.player.has_trait(Trait::PackRat) {
+            for _ in 0..2 {
+                self.player.add_item(Item::rnd());
+            }
+        }
+
+        if self.player.has_trait(Trait::Surveyor) {
+            let uuid = self.player.get_room().clone();
+            let doors: Vec<RoomDoor> = {
+                let room = self.room(&uuid)?;
+                (0..).take_while(|&idx| room.get_door(idx).is_some())
+                    .map(|idx| (uuid, idx))
+                    .filter(|door| self.links.contains_key(door))
+                    .collect()
+            };
+
+            self.known.extend(doors);
+        }
+
+        self.look_around()?;
+
+        Ok(Ui::Message(
+            MessageType::Static,
+            MessagePosition::Bottom,
+            format!("Welcome to the house. {}", self.enter_message()?)
+        ))
+    }
+
+    /// Returns the status bar and the side panel.
+    fn status(&self) -> Vec<Ui> {
+        let status = format!(
+            "{}   {}   turn {}",
+            self.player.get_name(),
+            text::capitalize(self.room_name(self.player.get_room())),
+            self.time
+        );
+
+        let mut side: Vec<String> = vec![String::from(self.player.get_name())];
+        side.extend(self.player.get_traits().iter().map(|t| String::from(t.get_name())));
+
+        side.push(String::new());
+        side.push(String::from("Carrying"));
+        match self.player.get_inventory().is_empty() {
+            true => side.push(String::from("  nothing")),
+            false => side.extend(self.player.get_inventory().iter()
+                .map(|item| format!("  {}", item.get_name()))
+            ),
+        }
+
+        side.push(String::new());
+        side.push(format!("{} rooms visited", self.visited.len()));
+        side.push(format!("camera: {}", self.camera.get_mode().key()));
+        side.push(format!("light: {}", self.lighting(self.player.get_room()).key()));
+
+        vec![Ui::Status(status), Ui::Side(side)]
+    }
+
+    fn room_name(&self, uuid: &Uuid) -> &str {
+        self.names.get(uuid).map_or("a room with no name", String::as_str)
+    }
+
+    /// Returns what the player notices on coming into their room.
+    fn enter_message(&self) -> Result<String, GameError> {
+        let uuid = self.player.get_room();
+        let room = self.room(uuid)?;
+
+        let doors = (0..).take_while(|&idx| room.get_door(idx).is_some()).count();
+        let npcs = self.npcs.iter().filter(|npc| npc.get_room() == uuid).count();
+        let items = self.floor.iter()
+            .filter(|&(&(ref room, _, _), _)| room == uuid)
+            .map(|(_, items)| items.len())
+            .sum();
+
+        Ok(format!(
+            "{}, you are in {}. {}{}",
+            self.player.get_name(),
+            self.room_name(uuid),
+            text::room_description(
+                room.get_kind(),
+                room.get_width(),
+                room.get_height(),
+                doors,
+                npcs,
+                items
+            ),
+            self.lighting(uuid).get_description()
+        ))
     }
 }
 
 impl Game for EndlessGame {
-    fn new(buf_width: usize, buf_height: usize) -> Self {
+    fn new(config: &Config, buf_width: usize, buf_height: usize) -> Result<Self, GameError> {
+        //  every game has a seed, so that it can be played again
+        let seed = config.seed.unwrap_or_else(random);
+        utils::seed(seed);
+        crash::set_seed(seed);
+
         let mut res = Self {
+            seed: seed,
             rooms: HashMap::new(),
             links: HashMap::new(),
             player: Player::nil(),
+            npcs: Vec::new(),
+            creation: Some(Creation::new()),
+            names: HashMap::new(),
+            trail: HashMap::new(),
+            known: HashSet::new(),
+            visited: Vec::new(),
+            locks: HashMap::new(),
+            marks: HashMap::new(),
+            lights: HashMap::new(),
+            explored: HashMap::new(),
+            floor: HashMap::new(),
+            inventory: None,
+            room_list: None,
+            map: None,
+            cursor: None,
+            travel: None,
+            time: 0,
+            camera: Camera::new(config.camera),
             buffer: None,
             buf_width: buf_width,
             buf_height: buf_height,
         };
 
-        let num_rooms = rnd_within::<usize>(5..11);
-        let num_links = rnd_within::<usize>(10..21);
+        let (min_rooms, max_rooms) = config.rooms;
+        let num_rooms = rnd_within::<usize>(min_rooms..max_rooms + 1);
+        let num_links = (num_rooms as f32 * config.link_density).round() as usize;
 
-        res.init(num_rooms, num_links);
-        res
+        res.init(config, num_rooms, num_links)?;
+        Ok(res)
     }
 
-    fn react(&mut self, input: InputType) -> Vec<Ui> {
+    fn react(&mut self, input: InputType) -> Result<Vec<Ui>, GameError> {
         let mut res = Vec::new();
 
+        //  whatever the input does, the view has to be worked out again
+        self.buffer.take();
+
+        //  nothing happens until the player knows who they are
+        if self.creation.is_some() {
+            match input {
+                InputType::FirstFrame => { return Ok(vec![self.name_prompt()]); },
+                InputType::Resize(..) => (),
+                input => { return self.react_creation(input); },
+            }
+        }
+
+        if self.inventory.is_some() {
+            match input {
+                InputType::Resize(..) => (),
+                input => { return self.react_inventory(input); },
+            }
+        }
+
+        if self.room_list.is_some() {
+            match input {
+                InputType::Resize(..) => (),
+                input => { return self.react_room_list(input); },
+            }
+        }
+
+        if self.map.is_some() {
+            match input {
+                InputType::Resize(..) => (),
+                input => { return self.react_map(input); },
+            }
+        }
+
+        if self.cursor.is_some() {
+            match input {
+                InputType::Resize(..) => (),
+                input => { return self.react_cursor(input); },
+            }
+        }
+
+        //  any key interrupts travelling
+        if self.travel.is_some() {
+            match input {
+                InputType::Resize(..) => (),
+                _ => {
+                    self.travel = None;
+                    return Ok(res);
+                },
+            }
+        }
+
         match input {
             InputType::FirstFrame => {
                 res.push(Ui::Message(
                     MessageType::Static,
                     MessagePosition::Bottom,
-                    format!(
-                        "Hello {}, you are now in room {}. {}",
-                        rnd_string(10),
-                        self.player.get_room().simple(),
-                        Self::mysterious_message()
-                    )
+                    self.enter_message()?
                 ));
             },
 
             InputType::Arrow(arrow) => {
-                let room = self.rooms.get(self.player.get_room()).unwrap();
                 let x = self.player.get_x();
                 let y = self.player.get_y();
 
@@ -167,38 +1540,58 @@ impl Game for EndlessGame {
                     ArrowType::Down => { ny = y + 1; },
                 };
 
-                match room.get_tile(nx, ny).map(|tile| &tile.base) {
-                    Some(&Base::Ground) => {
-                        self.player.set_x(nx);
-                        self.player.set_y(ny);
-                    },
+                res.extend(self.move_player(nx, ny)?.unwrap_or_default());
+            },
 
-                    Some(&Base::Door(idx)) => {
-                        let &(to_room, to_door) =
-                            self.links.get(&(self.player.get_room().clone(), idx)).unwrap();
+            //  wait a turn
+            InputType::Char('.') => {
+                self.pass_time(Action::Wait)?;
+            },
 
-                        self.player.set_room(to_room.clone());
-                        self.player.set_x(
-                            rnd_lt(self.rooms.get(&to_room).unwrap().get_width() - 2) + 1
-                        );
-                        self.player.set_y(
-                            rnd_lt(self.rooms.get(&to_room).unwrap().get_height() - 2) + 1
-                        );
+            InputType::Char('g') | InputType::Char(',') => {
+                res.extend(self.pick_up()?);
+            },
 
-                        res.push(Ui::Message(
-                            MessageType::Static,
-                            MessagePosition::Bottom,
-                            format!(
-                                "Hello {}, you are now in room {}. {}",
-                                rnd_string(10),
-                                to_room.simple(),
-                                Self::mysterious_message()
-                            )
-                        ));
-                    },
+            InputType::Char('i') => {
+                self.inventory = Some(0);
+                res.push(Ui::Panel(Some(self.inventory_panel())));
+            },
 
-                    _ => (),
-                }
+            InputType::Char('x') => {
+                res.extend(self.start_explore()?);
+            },
+
+            InputType::Char('c') => {
+                let mode = self.camera.get_mode().next();
+                self.camera.set_mode(mode);
+                res.push(Ui::Message(
+                    MessageType::Static,
+                    MessagePosition::Bottom,
+                    format!("Camera: {}.", mode.key())
+                ));
+            },
+
+            //  pick a room to go back to
+            InputType::Char('r') => {
+                self.room_list = Some(0);
+                res.push(Ui::Panel(Some(self.room_list_panel())));
+            },
+
+            //  look over the rooms seen so far, starting with this one
+            InputType::Char('m') => {
+                let here = self.visited.iter().position(|uuid| uuid == self.player.get_room());
+                self.map = Some(here.unwrap_or(0));
+                res.push(self.map_message()?);
+            },
+
+            //  pick a tile to travel to
+            InputType::Char('t') => {
+                self.cursor = Some((self.player.get_x(), self.player.get_y()));
+                res.push(Ui::Message(
+                    MessageType::Static,
+                    MessagePosition::Bottom,
+                    String::from("Travel where? arrows: move   tab: next door   enter: go   t: cancel")
+                ));
             },
 
             InputType::Resize(w, h) => {
@@ -210,47 +1603,155 @@ impl Game for EndlessGame {
             _ => (),
         };
 
-        res
+        Ok(res)
     }
 
-    fn gen_buffer(&mut self) -> Option<&Vec<Tile>> {
-        let room = self.rooms.get(self.player.get_room()).unwrap();
-        let mut tiles: Vec<Tile> = Vec::new();
+    fn tick(&mut self, elapsed: Duration) -> Result<Vec<Ui>, GameError> {
+        let mut res = Vec::new();
+
+        //  the view only has to be worked out again if something in it moved
+        if self.camera.tick(elapsed) {
+            self.buffer.take();
+        }
+
+        //  keep the status bar and the side panel up to date
+        if self.creation.is_none() {
+            res.extend(self.status());
+        }
+
+        //  the player walks on their own while travelling
+        if let Some(ref mut travel) = self.travel {
+            travel.wait += elapsed;
+        }
+
+        while self.travel.as_ref().map_or(false, |travel| travel.wait >= TRAVEL_STEP) {
+            self.travel.as_mut().unwrap().wait -= TRAVEL_STEP;
+            self.buffer.take();
+            res.extend(self.travel_step()?);
+        }
+
+        Ok(res)
+    }
+
+    fn wants_text(&self) -> bool {
+        self.creation.as_ref().map_or(false, |creation| creation.selected.is_none())
+    }
+
+    fn gen_buffer(&mut self) -> Result<Option<&Vec<Tile>>, GameError> {
+        if self.buffer.is_some() {
+            return Ok(self.buffer.as_ref());
+        }
+
+        if let Some(sel) = self.map {
+            self.buffer = Some(self.map_buffer(sel)?);
+            return Ok(self.buffer.as_ref());
+        }
 
         let px = self.player.get_x() as i32;
         let py = self.player.get_y() as i32;
         let bw = self.buf_width as i32;
         let bh = self.buf_height as i32;
 
-        let sx = px - bw / 2;
-        let sy = py - bh / 2;
-        let ex = px + (bw - bw / 2);
-        let ey = py + (bh - bh / 2);
+        let (sx, sy) = {
+            let room = self.room(self.player.get_room())?;
+            self.camera.view(
+                self.player.get_room(),
+                (px, py),
+                (room.get_width() as i32, room.get_height() as i32),
+                (bw, bh)
+            )
+        };
+        let ex = sx + bw;
+        let ey = sy + bh;
+
+        let room = self.room(self.player.get_room())?;
+        let mut tiles: Vec<Tile> = Vec::new();
+
+        let npcs: HashMap<(Uuid, usize, usize), Tile> = self.npcs.iter()
+            .map(|npc| ((npc.get_room().clone(), npc.get_x(), npc.get_y()), npc.get_tile()))
+            .collect();
+
+        //  the rooms beyond every open door
+        let portals: Vec<Portal> = (0..)
+            .take_while(|&idx| room.get_door(idx).is_some())
+            .map(|idx| (room.get_uuid(), idx))
+            .filter(|door| !self.locks.contains_key(door))
+            .filter(|door| room.get_door(door.1).map_or(false, |(x, y)| self.is_explored(&door.0, x, y)))
+            .filter_map(|door| {
+                let &(ref to, to_idx) = self.links.get(&door)?;
+                let to_room = self.rooms.get(to)?;
+                Portal::new(&**room, door.1, &**to_room, to_idx, (px as usize, py as usize))
+            })
+            .collect();
 
         for y in sy..ey {
             for x in sx..ex {
                 let ux = x as usize;
-                let uy = y as usize;;
+                let uy = y as usize;
+
+                //  which room shows here, and where in it
+                let spot = match (x >= 0 && y >= 0, room.get_tile(ux, uy)) {
+                    (true, Some(_)) => Some((room.get_uuid(), ux, uy)),
+                    _ => portals.iter()
+                        .filter_map(|portal| portal.look(x, y)
+                            .map(|(tx, ty)| (portal.get_room().clone(), tx, ty))
+                        )
+                        .find(|&(ref uuid, tx, ty)|
+                            self.rooms.get(uuid).map_or(false, |r| r.get_tile(tx, ty).is_some())
+                        ),
+                };
+
+                //  the player's own room shows only what they have seen, and
+                //  what's out of sight the way they remember it
+                let (spot, remembered) = match spot {
+                    Some((uuid, tx, ty)) if &uuid == self.player.get_room() =>
+                        match (self.in_view(tx, ty), self.is_explored(&uuid, tx, ty)) {
+                            (true, _) => (Some((uuid, tx, ty)), false),
+                            (false, true) => (Some((uuid, tx, ty)), true),
+                            (false, false) => (None, false),
+                        },
 
-                match x == px && y == py {
-                    true => {
-                        tiles.push(Tile::player());
-                        continue;
+                    //  through a door, only what's within sight and lit shows
+                    Some((uuid, tx, ty)) => match self.in_reach(x, y) && self.brightness(&uuid, tx, ty) > 0 {
+                        true => (Some((uuid, tx, ty)), false),
+                        false => (None, false),
                     },
-                    false => (),
-                }
 
-                match (x >= 0 && y >= 0, room.get_tile(ux, uy)) {
-                    (true, Some(ref t)) => { tiles.push((*t).clone()); },
-                    _ => { tiles.push(Tile::void()); },
+                    None => (None, false),
+                };
+
+                //  how much light falls on what's seen, where it can be felt
+                //  but not seen it's barely there
+                let light = match spot {
+                    Some((ref uuid, tx, ty)) if !remembered => cmp::max(self.brightness(uuid, tx, ty), TOUCH),
+                    _ => FULL,
+                };
+
+                let mut tile = match spot {
+                    _ if x == px && y == py => Tile::player(),
+                    Some(ref spot) if !remembered && npcs.contains_key(spot) => npcs[spot].clone(),
+                    Some(ref spot) => match self.top_item(spot) {
+                        Some(tile) => tile,
+                        None => self.room_tile(&spot.0, spot.1, spot.2)?,
+                    },
+                    None => Tile::void(),
+                };
+
+                if remembered {
+                    tile.remembered = true;
+                } else if !(x == px && y == py) {
+                    tile.light = light;
                 }
+
+                //  highlight the tile picked for travelling
+                tile.highlight = self.cursor == Some((ux, uy)) && x >= 0 && y >= 0;
+
+                tiles.push(tile);
             }
         }
 
-        {
-            mem::replace(&mut self.buffer, Some(tiles));
-        }
+        self.buffer = Some(tiles);
 
-        self.buffer.as_ref()
+        Ok(self.buffer.as_ref())
     }
 }
@@ -0,0 +1,190 @@
+use tile::Tile;
+use tile::base::Base;
+use tile::style::{ Style, Color };
+use game::endless::room::{ Room, neighbours };
+use game::endless::path::find_path;
+use game::endless::scheduler::{ Energy, TURN };
+
+use uuid::Uuid;
+
+use utils::rnd_lt;
+
+/// How an npc decides where to go.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum Behaviour {
+    /// Shuffles around at random, sometimes through a door.
+    Wander,
+
+    /// Heads for its target.
+    Follow,
+
+    /// Keeps away from its target.
+    Flee,
+}
+
+impl Behaviour {
+    pub fn rnd() -> Self {
+        match rnd_lt::<u8>(3) {
+            0 => Behaviour::Wander,
+            1 => Behaviour::Follow,
+            _ => Behaviour::Flee,
+        }
+    }
+}
+
+pub struct Npc {
+    room: Uuid,
+    x: usize,
+    y: usize,
+    glyph: char,
+
+    //  None looks however the theme draws npcs
+    style: Option<Style>,
+    behaviour: Behaviour,
+    energy: Energy,
+}
+
+impl Npc {
+    pub fn new(
+        room: Uuid,
+        x: usize,
+        y: usize,
+        glyph: char,
+        style: Option<Style>,
+        behaviour: Behaviour,
+        speed: i32
+    ) -> Self {
+        Self {
+            room: room,
+            x: x,
+            y: y,
+            glyph: glyph,
+            style: style,
+            behaviour: behaviour,
+            energy: Energy::new(speed),
+        }
+    }
+
+    /// Returns an npc with random looks and behaviour.
+    pub fn rnd(room: Uuid, x: usize, y: usize) -> Self {
+        let behaviour = Behaviour::rnd();
+
+        //  followers look a little more threatening than the rest
+        let glyph = match behaviour {
+            Behaviour::Wander => ['w', 'm', 'v'][rnd_lt::<usize>(3)],
+            Behaviour::Follow => ['F', 'G', 'K'][rnd_lt::<usize>(3)],
+            Behaviour::Flee => ['r', 'c', 's'][rnd_lt::<usize>(3)],
+        };
+
+        //  followers are the ones to keep an eye on, so they stand out
+        let style = match behaviour {
+            Behaviour::Follow => Some(Style::DEF_BOLD.with_fg(Color::RED)),
+            _ => None,
+        };
+
+        //  fleers are quick, followers a little slower than the player
+        let speed = match behaviour {
+            Behaviour::Wander => TURN / 2 + rnd_lt::<usize>(TURN as usize) as i32,
+            Behaviour::Follow => TURN * 3 / 4,
+            Behaviour::Flee => TURN + TURN / 4,
+        };
+
+        Self::new(room, x, y, glyph, style, behaviour, speed)
+    }
+
+    pub fn get_room(&self) -> &Uuid {
+        &self.room
+    }
+
+    pub fn get_x(&self) -> usize {
+        self.x
+    }
+
+    pub fn get_y(&self) -> usize {
+        self.y
+    }
+
+    pub fn get_behaviour(&self) -> Behaviour {
+        self.behaviour
+    }
+
+    pub fn get_energy_mut(&mut self) -> &mut Energy {
+        &mut self.energy
+    }
+
+    pub fn get_tile(&self) -> Tile {
+        Tile::npc(self.glyph, self.style)
+    }
+
+    pub fn set_room(&mut self, room: Uuid) {
+        self.room = room;
+    }
+
+    pub fn set_x(&mut self, x: usize) {
+        self.x = x;
+    }
+
+    pub fn set_y(&mut self, y: usize) {
+        self.y = y;
+    }
+
+    /// Picks the tile to step onto next, which may be a door, or None to stay
+    /// put. `target` is what the npc follows or flees from, and `is_free`
+    /// says whether nobody else stands on a tile.
+    pub fn choose_step<F>(
+        &self,
+        room: &dyn Room,
+        target: Option<(usize, usize)>,
+        is_free: F
+    ) -> Option<(usize, usize)>
+    where F: Fn(usize, usize) -> bool
+    {
+        let steps: Vec<(usize, usize)> = neighbours(self.x, self.y).into_iter()
+            .filter(|&(x, y)| is_free(x, y))
+            .filter(|&(x, y)| match room.get_tile(x, y) {
+                Some(tile) => tile.is_passable() || tile.has_base(&Base::Door(0)),
+                None => false,
+            })
+            .collect();
+
+        if steps.is_empty() {
+            return None;
+        }
+
+        let dist = |(x, y): (usize, usize), (tx, ty): (usize, usize)| {
+            (x as isize - tx as isize).abs() + (y as isize - ty as isize).abs()
+        };
+
+        match (self.behaviour, target) {
+            (Behaviour::Follow, Some(t)) => find_path(
+                room,
+                (self.x, self.y),
+                t,
+                |x, y, tile| tile.is_passable() && is_free(x, y)
+            )
+                .and_then(|path| path.first().cloned())
+                .filter(|&(x, y)| is_free(x, y)),
+
+            (Behaviour::Flee, Some(t)) => steps.into_iter()
+                .filter(|&s| dist(s, t) > dist((self.x, self.y), t))
+                .max_by_key(|&s| dist(s, t)),
+
+            _ => {
+                //  wanderers dawdle, and only rarely leave the room
+                if rnd_lt::<u8>(3) == 0 {
+                    return None;
+                }
+
+                let steps: Vec<_> = steps.into_iter()
+                    .filter(|&(x, y)| rnd_lt::<u8>(8) == 0 ||
+                        room.get_tile(x, y).map_or(false, Tile::is_passable))
+                    .collect();
+
+                match steps.is_empty() {
+                    true => None,
+                    false => Some(steps[rnd_lt(steps.len())]),
+                }
+            },
+        }
+    }
+}
@@ -0,0 +1,252 @@
+//  A* over the tiles of a single room. What counts as passable is up to the
+//  caller, so the player, npcs and auto-explore can all share this
+
+use tile::Tile;
+use tile::base::Base;
+use game::endless::room::{ Room, neighbours };
+
+use std::cmp::Ordering;
+use std::collections::{ BinaryHeap, HashMap };
+
+#[derive(PartialEq, Eq)]
+struct Node {
+    //  estimated total cost through this node
+    cost: usize,
+    pos: (usize, usize),
+}
+
+//  BinaryHeap is a max-heap, so order nodes backwards to pop the cheapest
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.cmp(&self.cost).then_with(|| self.pos.cmp(&other.pos))
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn dist(a: (usize, usize), b: (usize, usize)) -> usize {
+    let dx = (a.0 as isize - b.0 as isize).abs();
+    let dy = (a.1 as isize - b.1 as isize).abs();
+    (dx + dy) as usize
+}
+
+/// Returns whether the player may walk over given tile on the way somewhere.
+pub fn walkable(_x: usize, _y: usize, tile: &Tile) -> bool {
+    tile.is_passable()
+}
+
+/// Returns whether a path may end on given tile even if it isn't passable:
+/// doors, and floor that only an actor is in the way of.
+pub fn can_end(tile: &Tile) -> bool {
+    tile.is_passable() || tile.has_base(&Base::Door(0))
+}
+
+/// Returns the shortest path from `from` to `to`, not including `from`, or
+/// None if there is none. Every tile on the way must satisfy `passable`,
+/// except `to` itself, so paths can end at doors or other actors, but never
+/// in a wall.
+pub fn find_path<F>(
+    room: &dyn Room,
+    from: (usize, usize),
+    to: (usize, usize),
+    passable: F
+) -> Option<Vec<(usize, usize)>>
+where F: Fn(usize, usize, &Tile) -> bool
+{
+    if from == to {
+        return Some(Vec::new());
+    }
+
+    if !room.get_tile(to.0, to.1).map_or(false, can_end) {
+        return None;
+    }
+
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<(usize, usize), (usize, usize)> = HashMap::new();
+    let mut costs: HashMap<(usize, usize), usize> = HashMap::new();
+
+    costs.insert(from, 0);
+    open.push(Node { cost: dist(from, to), pos: from });
+
+    while let Some(Node { pos, .. }) = open.pop() {
+        if pos == to {
+            let mut res = vec![to];
+            let mut cur = to;
+            while let Some(&prev) = came_from.get(&cur) {
+                if prev == from {
+                    break;
+                }
+                res.push(prev);
+                cur = prev;
+            }
+
+            res.reverse();
+            return Some(res);
+        }
+
+        let cost = costs[&pos] + 1;
+
+        for (nx, ny) in neighbours(pos.0, pos.1) {
+            let ok = match room.get_tile(nx, ny) {
+                Some(tile) if (nx, ny) == to => can_end(tile),
+                Some(tile) => passable(nx, ny, tile),
+                None => false,
+            };
+
+            if !ok || costs.get(&(nx, ny)).map_or(false, |&c| c <= cost) {
+                continue;
+            }
+
+            costs.insert((nx, ny), cost);
+            came_from.insert((nx, ny), pos);
+            open.push(Node { cost: cost + dist((nx, ny), to), pos: (nx, ny) });
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::GameError;
+
+    use uuid::Uuid;
+
+    //  a room drawn as text: `#` wall, `.` floor, `~` water and `d` door 0
+    struct Drawn {
+        width: usize,
+        tiles: Vec<Tile>,
+    }
+
+    impl Drawn {
+        fn from(rows: &[&str]) -> Self {
+            Self {
+                width: rows[0].len(),
+                tiles: rows.iter()
+                    .flat_map(|row| row.chars())
+                    .map(|ch| match ch {
+                        '#' => Tile::wall(),
+                        '~' => Tile::water(),
+                        'd' => Tile::door(0),
+                        _ => Tile::ground(),
+                    })
+                    .collect(),
+            }
+        }
+    }
+
+    impl Room for Drawn {
+        fn new(_: Uuid, _: usize, _: usize, _: usize) -> Result<Self, GameError> {
+            unreachable!()
+        }
+
+        fn get_uuid(&self) -> Uuid {
+            Uuid::nil()
+        }
+
+        fn get_kind(&self) -> &'static str {
+            "drawn"
+        }
+
+        fn get_width(&self) -> usize {
+            self.width
+        }
+
+        fn get_height(&self) -> usize {
+            self.tiles.len() / self.width
+        }
+
+        fn get_tile(&self, x: usize, y: usize) -> Option<&Tile> {
+            match x < self.width {
+                true => self.tiles.get(y * self.width + x),
+                false => None,
+            }
+        }
+    }
+
+    //  every step goes to a neighbour that may be walked on
+    fn check_steps(room: &Drawn, from: (usize, usize), path: &[(usize, usize)]) {
+        let mut at = from;
+        for (i, &step) in path.iter().enumerate() {
+            assert_eq!(dist(at, step), 1);
+            if i + 1 < path.len() {
+                assert!(room.get_tile(step.0, step.1).unwrap().is_passable());
+            }
+            at = step;
+        }
+    }
+
+    #[test]
+    fn straight_line() {
+        let room = Drawn::from(&[
+            "#####",
+            "#...#",
+            "#####",
+        ]);
+
+        assert_eq!(find_path(&room, (1, 1), (3, 1), walkable), Some(vec![(2, 1), (3, 1)]));
+        assert_eq!(find_path(&room, (1, 1), (1, 1), walkable), Some(vec![]));
+    }
+
+    #[test]
+    fn around_water() {
+        let room = Drawn::from(&[
+            "#######",
+            "#.....#",
+            "#.~~~.#",
+            "#.~.~.#",
+            "#.....#",
+            "#######",
+        ]);
+
+        let path = find_path(&room, (1, 3), (5, 3), walkable).unwrap();
+        assert_eq!(path.len(), 6);
+        assert_eq!(path.last(), Some(&(5, 3)));
+        check_steps(&room, (1, 3), &path);
+    }
+
+    #[test]
+    fn ends_at_doors_but_not_walls() {
+        let room = Drawn::from(&[
+            "##d##",
+            "#...#",
+            "#####",
+        ]);
+
+        assert_eq!(find_path(&room, (1, 1), (2, 0), walkable), Some(vec![(2, 1), (2, 0)]));
+        assert_eq!(find_path(&room, (1, 1), (0, 1), walkable), None);
+    }
+
+    #[test]
+    fn nothing_through_walls() {
+        let room = Drawn::from(&[
+            "#####",
+            "#.#.#",
+            "#####",
+        ]);
+
+        assert_eq!(find_path(&room, (1, 1), (3, 1), walkable), None);
+    }
+
+    #[test]
+    fn passable_is_up_to_the_caller() {
+        let room = Drawn::from(&[
+            "#####",
+            "#...#",
+            "#...#",
+            "#####",
+        ]);
+
+        //  something stands in the way, so the path goes round it
+        let path = find_path(&room, (1, 1), (3, 1), |x, y, tile| (x, y) != (2, 1) && walkable(x, y, tile))
+            .unwrap();
+        assert_eq!(path.len(), 4);
+        assert!(!path.contains(&(2, 1)));
+        check_steps(&room, (1, 1), &path);
+    }
+}
@@ -1,9 +1,22 @@
+use game::endless::character::Trait;
+use game::endless::item::Item;
+use game::endless::scheduler::{ Energy, TURN };
+
 use uuid::Uuid;
 
+/// Who the player is until they say, e.g. in a world exported without playing.
+const NO_NAME: &str = "Nobody";
+
 pub struct Player {
     room: Uuid,
     x: usize,
     y: usize,
+    energy: Energy,
+    inventory: Vec<Item>,
+
+    //  what the player made of themselves at the start of the run
+    name: String,
+    traits: Vec<Trait>,
 }
 
 impl Player {
@@ -12,6 +25,10 @@ impl Player {
             room: room,
             x: x,
             y: y,
+            energy: Energy::new(TURN),
+            inventory: Vec::new(),
+            name: String::from(NO_NAME),
+            traits: Vec::new(),
         }
     }
 
@@ -20,6 +37,10 @@ impl Player {
             room: Uuid::nil(),
             x: 0,
             y: 0,
+            energy: Energy::new(TURN),
+            inventory: Vec::new(),
+            name: String::from(NO_NAME),
+            traits: Vec::new(),
         }
     }
 
@@ -35,6 +56,56 @@ impl Player {
         self.y
     }
 
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_traits(&self) -> &Vec<Trait> {
+        &self.traits
+    }
+
+    pub fn has_trait(&self, t: Trait) -> bool {
+        self.traits.contains(&t)
+    }
+
+    pub fn get_energy(&self) -> &Energy {
+        &self.energy
+    }
+
+    pub fn get_energy_mut(&mut self) -> &mut Energy {
+        &mut self.energy
+    }
+
+    pub fn get_inventory(&self) -> &Vec<Item> {
+        &self.inventory
+    }
+
+    pub fn add_item(&mut self, item: Item) {
+        self.inventory.push(item);
+    }
+
+    pub fn remove_item(&mut self, index: usize) -> Option<Item> {
+        match index < self.inventory.len() {
+            true => Some(self.inventory.remove(index)),
+            false => None,
+        }
+    }
+
+    pub fn set_name(&mut self, name: &str) {
+        self.name = String::from(name);
+    }
+
+    /// Gives the player given traits, and whatever speed they come with.
+    pub fn set_traits(&mut self, traits: &[Trait]) {
+        self.traits = traits.to_vec();
+
+        let speed = match self.has_trait(Trait::Quick) {
+            true => TURN * 5 / 4,
+            false => TURN,
+        };
+        self.energy = Energy::new(speed);
+    }
+
     pub fn set_room(&mut self, room: Uuid) {
         self.room = room;
     }
@@ -0,0 +1,95 @@
+//  seeing through open doors. The room a door leads to is drawn beyond it,
+//  turned so that its own door lies on top of this one with its inside facing
+//  away, but only where the player could see it through the doorway. Links
+//  don't have to make sense, so neither does what shows up
+
+use game::endless::room::{ Room, entrance };
+
+use uuid::Uuid;
+
+pub struct Portal {
+    room: Uuid,
+
+    //  where the player stands and where the door is, in the player's room
+    eye: (i32, i32),
+    door: (i32, i32),
+
+    //  the way out through the door
+    facing: (i32, i32),
+
+    //  the door on the far side, in the room it leads to
+    far_door: (i32, i32),
+
+    //  quarter turns from the player's room to the far one
+    turns: usize,
+}
+
+impl Portal {
+    /// Returns the portal through door `index` of room `from`, as seen from
+    /// given position, onto door `to_index` of room `to`.
+    pub fn new(
+        from: &dyn Room,
+        index: usize,
+        to: &dyn Room,
+        to_index: usize,
+        eye: (usize, usize)
+    ) -> Option<Self> {
+        let (door, facing) = door_facing(from, index)?;
+        let (far_door, far_facing) = door_facing(to, to_index)?;
+
+        //  going out through this door is coming in through the far one
+        let turns = (0..4).find(|&n| turn(facing, n) == (-far_facing.0, -far_facing.1))?;
+
+        Some(Self {
+            room: to.get_uuid(),
+            eye: (eye.0 as i32, eye.1 as i32),
+            door: door,
+            facing: facing,
+            far_door: far_door,
+            turns: turns,
+        })
+    }
+
+    pub fn get_room(&self) -> &Uuid {
+        &self.room
+    }
+
+    /// Returns the position in the far room that shows at given position
+    /// beyond the door, or None if it can't be seen through the doorway.
+    pub fn look(&self, x: i32, y: i32) -> Option<(usize, usize)> {
+        let (fx, fy) = self.facing;
+        let dot = |(ax, ay): (i32, i32)| ax * fx + ay * fy;
+        let side = |(ax, ay): (i32, i32)| ay * fx - ax * fy;
+
+        let to_door = (self.door.0 - self.eye.0, self.door.1 - self.eye.1);
+        let to_pos = (x - self.eye.0, y - self.eye.1);
+
+        //  only what lies beyond the wall, and only where the line from the
+        //  eye crosses the wall within the doorway
+        let (near, far) = (dot(to_door), dot(to_pos));
+        if far <= near || 2 * (side(to_pos) * near - side(to_door) * far).abs() > far {
+            return None;
+        }
+
+        let (rx, ry) = turn((x - self.door.0, y - self.door.1), self.turns);
+        let (tx, ty) = (self.far_door.0 + rx, self.far_door.1 + ry);
+
+        match tx >= 0 && ty >= 0 {
+            true => Some((tx as usize, ty as usize)),
+            false => None,
+        }
+    }
+}
+
+//  where the door with given index is, and which way is out through it
+fn door_facing(room: &dyn Room, index: usize) -> Option<((i32, i32), (i32, i32))> {
+    let (x, y) = room.get_door(index)?;
+    let (ex, ey) = entrance(room, index)?;
+
+    Some(((x as i32, y as i32), (x as i32 - ex as i32, y as i32 - ey as i32)))
+}
+
+//  given direction turned a quarter clockwise given number of times
+fn turn((x, y): (i32, i32), turns: usize) -> (i32, i32) {
+    (0..turns).fold((x, y), |(x, y), _| (-y, x))
+}
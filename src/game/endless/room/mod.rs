@@ -1,19 +1,82 @@
 pub mod square;
 
 use tile::Tile;
+use tile::base::Base;
+use game::GameError;
+use game::endless::room::square::SquareRoom;
 
 use uuid::Uuid;
 
+/// Every kind of room there is.
+pub const KINDS: [&str; 1] = ["square"];
+
 pub trait Room {
+    /// Returns a new room, or an error if given doors don't fit.
     fn new(
         uuid: Uuid,
         num_doors: usize,
         width: usize,
         height: usize
-    ) -> Self where Self: Sized;
+    ) -> Result<Self, GameError> where Self: Sized;
 
     fn get_uuid(&self) -> Uuid;
+
+    /// Returns what kind of room this is, e.g. "square".
+    fn get_kind(&self) -> &'static str;
+
     fn get_width(&self) -> usize;
     fn get_height(&self) -> usize;
     fn get_tile(&self, x: usize, y: usize) -> Option<&Tile>;
+
+    /// Returns the position of the door with given index.
+    fn get_door(&self, index: usize) -> Option<(usize, usize)> {
+        for y in 0..self.get_height() {
+            for x in 0..self.get_width() {
+                match self.get_tile(x, y).map(|tile| &tile.base) {
+                    Some(&Base::Door(idx)) if idx == index => {
+                        return Some((x, y));
+                    },
+                    _ => (),
+                }
+            }
+        }
+
+        None
+    }
+}
+
+/// Returns a new room of given kind, which should be one of `KINDS`.
+pub fn build(
+    kind: &str,
+    uuid: Uuid,
+    num_doors: usize,
+    width: usize,
+    height: usize
+) -> Result<Box<dyn Room>, GameError> {
+    match kind {
+        "square" => Ok(Box::new(SquareRoom::new(uuid, num_doors, width, height)?)),
+        _ => Err(GameError::Generation(format!("unknown room kind `{}`", kind))),
+    }
+}
+
+/// Returns the passable tile just inside the door with given index, where
+/// anything coming through that door ends up.
+pub fn entrance(room: &dyn Room, index: usize) -> Option<(usize, usize)> {
+    let (x, y) = room.get_door(index)?;
+
+    neighbours(x, y).into_iter()
+        .find(|&(nx, ny)| room.get_tile(nx, ny).map_or(false, Tile::is_passable))
+}
+
+/// Returns the positions left of, right of, above and below given position
+/// that don't underflow.
+pub fn neighbours(x: usize, y: usize) -> Vec<(usize, usize)> {
+    let mut res = vec![(x + 1, y), (x, y + 1)];
+    if x > 0 {
+        res.push((x - 1, y));
+    }
+    if y > 0 {
+        res.push((x, y - 1));
+    }
+    res
 }
@@ -1,9 +1,13 @@
 use tile::Tile;
+use game::GameError;
 use game::endless::room::Room;
 
 use std::cmp;
 use uuid::Uuid;
-use rand::prelude::*;
+use utils::{ rnd, rnd_lt, rnd_within };
+
+/// How likely a room big enough for one is to have a pool in it.
+const POOL_CHANCE: f32 = 0.25;
 
 pub struct SquareRoom {
     uuid: Uuid,
@@ -13,75 +17,115 @@ pub struct SquareRoom {
 }
 
 impl SquareRoom {
-    fn fill_tiles(&mut self, width: usize, height: usize, num_doors: usize) {
-        let size = width * height;
-
-        let mut num_doors_left = num_doors;
-        let mut num_walls_left = width * 2 + height * 2 - 8;
-
-        for y in 0..height {
-            for x in 0..width {
-                let is_wall = (x == 0) || (x == width - 1) || (y == 0) || (y == height - 1);
-
-                let min_clamp = cmp::min(
-                    size / 2,
-                    num_walls_left - num_doors_left
-                );
-                let valid_door = self.is_valid_door_position(x, y);
-                let is_door =
-                    valid_door &&
-                    num_doors_left > 0 &&
-                    random::<usize>() % min_clamp == 0;
-
-                match is_wall {
-                    false => { self.tiles.push(Tile::ground()); },
-                    true => {
-                        match is_door {
-                            false => { self.tiles.push(Tile::wall()); },
-                            true => {
-                                self.tiles.push(Tile::door(num_doors - num_doors_left));
-
-                                if num_doors_left > 0 {
-                                    num_doors_left -= 1;
-                                }
-                            },
-                        };
-
-                        if valid_door && num_walls_left > 0 {
-                            num_walls_left -= 1;
-                        }
-                    },
-                }
+    //  doors go anywhere along a wall but the corners, with some wall between
+    //  any two of them, so a wall of given length fits every other tile
+    fn wall_capacity(len: usize) -> usize {
+        (len - 1) / 2
+    }
+
+    fn capacity(width: usize, height: usize) -> usize {
+        2 * Self::wall_capacity(width) + 2 * Self::wall_capacity(height)
+    }
+
+    //  position of the `i`th tile along a wall, corners not counted, going
+    //  clockwise from the top
+    fn wall_tile(&self, wall: usize, i: usize) -> (usize, usize) {
+        match wall {
+            0 => (1 + i, 0),
+            1 => (self.width - 1, 1 + i),
+            2 => (self.width - 2 - i, self.height - 1),
+            _ => (0, self.height - 2 - i),
+        }
+    }
+
+    /// Returns where given number of doors go: dealt round the walls one at a
+    /// time, starting from a random one, then spread evenly along each wall.
+    fn place_doors(&self, num_doors: usize) -> Vec<(usize, usize)> {
+        let lens = [self.width - 2, self.height - 2, self.width - 2, self.height - 2];
+        let (across, down) = (Self::wall_capacity(self.width), Self::wall_capacity(self.height));
+        let caps = [across, down, across, down];
+
+        let mut counts = [0; 4];
+        let mut wall = rnd_lt(4);
+        for _ in 0..num_doors {
+            while counts[wall] == caps[wall] {
+                wall = (wall + 1) % 4;
             }
+
+            counts[wall] += 1;
+            wall = (wall + 1) % 4;
         }
 
-        if num_doors_left > 0 {
-            panic!("need doors");
-        };
+        let mut res = Vec::new();
+        for wall in 0..4 {
+            let (len, n) = (lens[wall], counts[wall]);
+
+            //  the middle of each of `n` equal stretches of wall, pushed along
+            //  if it would touch the door before
+            let mut first_free = 0;
+            for i in 0..n {
+                let at = cmp::max((2 * i + 1) * len / (2 * n), first_free);
+                res.push(self.wall_tile(wall, at));
+                first_free = at + 2;
+            }
+        }
+
+        res
     }
 
-    fn is_valid_door_position(&self, x: usize, y: usize) -> bool {
-        //  can't place door in corner
-        if (x == 0 || x == self.width - 1) && (y == 0 || y == self.height - 1) {
-            return false;
+    /// Returns the left, top, width and height of a pool, now and then, with
+    /// a path of floor left all round it so every door can still be reached.
+    fn place_pool(&self) -> Option<(usize, usize, usize, usize)> {
+        if self.width < 7 || self.height < 7 || rnd::<f32>() >= POOL_CHANCE {
+            return None;
         }
 
-        true
-        /*
-        //  can't place door next to another door
-        match self.get_tile(x - 1, y)
-            .or_else(|| self.get_tile(x + 1, y))
-            .or_else(|| self.get_tile(x, y - 1))
-            .or_else(|| self.get_tile(x, y + 1))
-        {
-            Some(ref tile) => tile.has_base(&Base::Door(42)),
-            None => true,
-        }*/
+        let (w, h) = (rnd_within(1..self.width - 3), rnd_within(1..self.height - 3));
+        let (x, y) = (rnd_within(2..self.width - 1 - w), rnd_within(2..self.height - 1 - h));
+        Some((x, y, w, h))
+    }
+
+    fn fill_tiles(&mut self, num_doors: usize) {
+        let mut doors = self.place_doors(num_doors);
+        let pool = self.place_pool();
+        let in_pool = |x, y| pool.map_or(false, |(px, py, pw, ph)|
+            x >= px && x < px + pw && y >= py && y < py + ph
+        );
+
+        //  numbered in reading order, same as they're found
+        doors.sort_by_key(|&(x, y)| (y, x));
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let is_wall = (x == 0) || (x == self.width - 1) || (y == 0) || (y == self.height - 1);
+
+                let tile = match doors.iter().position(|&door| door == (x, y)) {
+                    Some(index) => Tile::door(index),
+                    None => match is_wall {
+                        true => Tile::wall(),
+                        false if in_pool(x, y) => Tile::water(),
+                        false => Tile::ground(),
+                    },
+                };
+
+                self.tiles.push(tile);
+            }
+        }
     }
 }
 
 impl Room for SquareRoom {
-    fn new(uuid: Uuid, num_doors: usize, width: usize, height: usize) -> Self {
+    fn new(uuid: Uuid, num_doors: usize, width: usize, height: usize) -> Result<Self, GameError> {
+        //  a room too small for its doors grows along its shorter side until
+        //  they fit
+        let (mut width, mut height) = (cmp::max(width, 3), cmp::max(height, 3));
+        while Self::capacity(width, height) < num_doors {
+            match width <= height {
+                true => width += 1,
+                false => height += 1,
+            }
+        }
+
         let mut res = Self {
             uuid: uuid,
             width: width,
@@ -89,14 +133,18 @@ impl Room for SquareRoom {
             tiles: Vec::new(),
         };
 
-        res.fill_tiles(width, height, num_doors);
-        res
+        res.fill_tiles(num_doors);
+        Ok(res)
     }
 
     fn get_uuid(&self) -> Uuid {
         self.uuid.clone()
     }
 
+    fn get_kind(&self) -> &'static str {
+        "square"
+    }
+
     fn get_width(&self) -> usize {
         self.width
     }
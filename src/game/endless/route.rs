@@ -0,0 +1,156 @@
+//  Dijkstra over rooms, following only the links the player already went
+//  through. Where the player lands after a door is random, so crossing any
+//  room but the first is costed from the middle of that room
+
+use game::endless::RoomDoor;
+use game::endless::room::Room;
+use game::endless::path::{ find_path, walkable };
+use game::endless::scheduler::{ Action, TURN };
+
+use uuid::Uuid;
+
+use std::cmp::Reverse;
+use std::collections::{ BinaryHeap, HashMap, HashSet };
+
+/// Returns the doors to go through, in order, to get from given position to
+/// the room `to`, or None if the player doesn't know the way.
+pub fn find_route(
+    rooms: &HashMap<Uuid, Box<dyn Room>>,
+    links: &HashMap<RoomDoor, RoomDoor>,
+    known: &HashSet<RoomDoor>,
+    from: (Uuid, usize, usize),
+    to: &Uuid
+) -> Option<Vec<RoomDoor>> {
+    let (start, sx, sy) = from;
+
+    let mut open = BinaryHeap::new();
+    let mut costs: HashMap<Uuid, usize> = HashMap::new();
+    let mut came_by: HashMap<Uuid, RoomDoor> = HashMap::new();
+
+    costs.insert(start, 0);
+    open.push(Reverse((0, start)));
+
+    while let Some(Reverse((cost, uuid))) = open.pop() {
+        if uuid == *to {
+            let mut res = Vec::new();
+            let mut cur = uuid;
+            while let Some(door) = came_by.get(&cur) {
+                res.push(door.clone());
+                cur = door.0;
+            }
+
+            res.reverse();
+            return Some(res);
+        }
+
+        if costs.get(&uuid).map_or(false, |&c| c < cost) {
+            continue;
+        }
+
+        let room = rooms.get(&uuid)?;
+        let pos = match uuid == start {
+            true => (sx, sy),
+            false => (room.get_width() / 2, room.get_height() / 2),
+        };
+
+        for idx in 0.. {
+            let door = match room.get_door(idx) {
+                Some(door) => door,
+                None => break,
+            };
+
+            let &(next, _) = match links.get(&(uuid, idx)) {
+                Some(link) if known.contains(&(uuid, idx)) => link,
+                _ => continue,
+            };
+
+            let steps = match find_path(&**room, pos, door, walkable) {
+                Some(path) => path.len(),
+                None => continue,
+            };
+
+            let next_cost = cost + steps + (Action::Door.cost() / TURN) as usize;
+            if costs.get(&next).map_or(false, |&c| c <= next_cost) {
+                continue;
+            }
+
+            costs.insert(next, next_cost);
+            came_by.insert(next, (uuid, idx));
+            open.push(Reverse((next_cost, next)));
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use game::endless::room::square::SquareRoom;
+
+    //  small square rooms, too small for pools, so every door can be reached
+    fn rooms(doors: &[usize]) -> (Vec<Uuid>, HashMap<Uuid, Box<dyn Room>>) {
+        let uuids: Vec<Uuid> = doors.iter().map(|_| Uuid::new_v4()).collect();
+        let rooms = uuids.iter().zip(doors.iter())
+            .map(|(&uuid, &num_doors)| {
+                let room: Box<dyn Room> = Box::new(SquareRoom::new(uuid, num_doors, 5, 5).unwrap());
+                (uuid, room)
+            })
+            .collect();
+
+        (uuids, rooms)
+    }
+
+    fn link(links: &mut HashMap<RoomDoor, RoomDoor>, a: RoomDoor, b: RoomDoor) {
+        links.insert(a, b);
+        links.insert(b, a);
+    }
+
+    #[test]
+    fn through_the_middle() {
+        let (uuids, rooms) = rooms(&[1, 2, 1]);
+        let mut links = HashMap::new();
+        link(&mut links, (uuids[0], 0), (uuids[1], 0));
+        link(&mut links, (uuids[1], 1), (uuids[2], 0));
+        let known: HashSet<RoomDoor> = links.keys().cloned().collect();
+
+        assert_eq!(
+            find_route(&rooms, &links, &known, (uuids[0], 2, 2), &uuids[2]),
+            Some(vec![(uuids[0], 0), (uuids[1], 1)])
+        );
+        assert_eq!(find_route(&rooms, &links, &known, (uuids[0], 2, 2), &uuids[0]), Some(vec![]));
+    }
+
+    #[test]
+    fn only_known_doors() {
+        let (uuids, rooms) = rooms(&[1, 2, 1]);
+        let mut links = HashMap::new();
+        link(&mut links, (uuids[0], 0), (uuids[1], 0));
+        link(&mut links, (uuids[1], 1), (uuids[2], 0));
+
+        //  been from the first room to the second and back, no further
+        let known: HashSet<RoomDoor> = [(uuids[0], 0), (uuids[1], 0)].iter().cloned().collect();
+
+        assert_eq!(find_route(&rooms, &links, &known, (uuids[0], 2, 2), &uuids[2]), None);
+        assert_eq!(find_route(&rooms, &links, &known, (uuids[2], 2, 2), &uuids[0]), None);
+        assert_eq!(
+            find_route(&rooms, &links, &known, (uuids[1], 2, 2), &uuids[0]),
+            Some(vec![(uuids[1], 0)])
+        );
+    }
+
+    #[test]
+    fn shortcuts_win() {
+        let (uuids, rooms) = rooms(&[2, 2, 2]);
+        let mut links = HashMap::new();
+        link(&mut links, (uuids[0], 0), (uuids[1], 0));
+        link(&mut links, (uuids[1], 1), (uuids[2], 0));
+        link(&mut links, (uuids[0], 1), (uuids[2], 1));
+        let known: HashSet<RoomDoor> = links.keys().cloned().collect();
+
+        assert_eq!(
+            find_route(&rooms, &links, &known, (uuids[0], 2, 2), &uuids[2]),
+            Some(vec![(uuids[0], 1)])
+        );
+    }
+}
@@ -0,0 +1,57 @@
+//  everything that acts has some energy. Each time step it gains energy equal
+//  to its speed, and it may act whenever it has at least `TURN` energy; the
+//  action then costs some energy, so fast actors act more often and slow
+//  actions make you wait longer for your next turn
+
+/// How much energy an actor needs to act, and what a plain step costs.
+pub const TURN: i32 = 100;
+
+/// Actors in rooms the player is not in only get to act once every this many
+/// time steps, spending everything they saved up at once.
+pub const COARSE_STEPS: u64 = 10;
+
+/// Something an actor can do.
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum Action {
+    Step,
+    Door,
+    Wait,
+}
+
+impl Action {
+    pub fn cost(&self) -> i32 {
+        match self {
+            &Action::Step => TURN,
+            &Action::Door => TURN * 3 / 2,
+            &Action::Wait => TURN,
+        }
+    }
+}
+
+pub struct Energy {
+    speed: i32,
+    energy: i32,
+}
+
+impl Energy {
+    /// Returns energy for an actor with given speed, who may act right away.
+    pub fn new(speed: i32) -> Self {
+        Self {
+            speed: speed,
+            energy: TURN,
+        }
+    }
+
+    /// Gains one time step's worth of energy.
+    pub fn gain(&mut self) {
+        self.energy += self.speed;
+    }
+
+    pub fn can_act(&self) -> bool {
+        self.energy >= TURN
+    }
+
+    pub fn spend(&mut self, action: Action) {
+        self.energy -= action.cost();
+    }
+}
@@ -0,0 +1,177 @@
+//  made-up text. A grammar is a set of rules, each a list of choices, and
+//  `{rule}` inside a choice expands to one of that rule's choices, picked at
+//  random, so
+//
+//      name = ["the {adj} hall"]
+//      adj  = ["dusty", "quiet"]
+//
+//  expands `{name}` to "the dusty hall" or "the quiet hall"
+
+use utils::rnd_lt;
+
+use std::collections::HashMap;
+
+/// How deep rules may expand into each other before we give up.
+const MAX_DEPTH: usize = 8;
+
+pub struct Grammar {
+    rules: HashMap<&'static str, Vec<&'static str>>,
+}
+
+impl Grammar {
+    pub fn new() -> Self {
+        Self {
+            rules: HashMap::new(),
+        }
+    }
+
+    /// Returns the grammar with given rule added, or replaced.
+    pub fn rule(mut self, name: &'static str, choices: &[&'static str]) -> Self {
+        self.rules.insert(name, choices.to_vec());
+        self
+    }
+
+    /// Expands every `{rule}` in given text.
+    pub fn expand(&self, text: &str) -> String {
+        self.expand_at(text, 0)
+    }
+
+    fn expand_at(&self, text: &str, depth: usize) -> String {
+        let mut res = String::new();
+        let mut rest = text;
+
+        while let Some(start) = rest.find('{') {
+            let end = match rest[start..].find('}') {
+                Some(end) => start + end,
+                None => break,
+            };
+
+            res.push_str(&rest[..start]);
+
+            let name = &rest[start + 1..end];
+            match (self.rules.get(name), depth < MAX_DEPTH) {
+                (Some(choices), true) if !choices.is_empty() => {
+                    let choice = choices[rnd_lt(choices.len())];
+                    res.push_str(&self.expand_at(choice, depth + 1));
+                },
+                _ => res.push_str(name),
+            }
+
+            rest = &rest[end + 1..];
+        }
+
+        res.push_str(rest);
+        res
+    }
+}
+
+/// Returns given text starting with a capital letter.
+pub fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+//  how big a room feels, going by its floor
+fn size_word(width: usize, height: usize) -> &'static str {
+    match width * height {
+        0..=120 => "small",
+        121..=170 => "medium",
+        _ => "large",
+    }
+}
+
+fn base_grammar(width: usize, height: usize) -> Grammar {
+    let grammar = Grammar::new()
+        .rule("adj", &[
+            "dusty", "crooked", "humming", "quiet", "tilted", "pale", "velvet",
+            "forgotten", "narrow", "sunken", "flickering", "patient", "borrowed",
+        ])
+        .rule("of", &[
+            "lost letters", "the small hours", "second thoughts", "stopped clocks",
+            "echoes", "unfinished stairs", "wet paint", "old birthdays", "whispers",
+        ])
+        .rule("owner", &[
+            "your grandmother", "nobody", "the last tenant", "someone else",
+            "the caretaker", "a stranger",
+        ])
+        .rule("smell", &[
+            "dust", "rain", "candle wax", "oranges", "pencil shavings", "old books",
+            "cold tea",
+        ])
+        .rule("sound", &[
+            "a clock ticking somewhere", "the house settling", "a radio in another room",
+            "your own breathing", "nothing at all", "water in the walls",
+        ]);
+
+    match size_word(width, height) {
+        "small" => grammar.rule("room", &["closet", "pantry", "alcove", "nook", "box room"]),
+        "medium" => grammar.rule("room", &["parlour", "study", "bedroom", "gallery", "kitchen"]),
+        _ => grammar.rule("room", &["hall", "ballroom", "atrium", "library", "chapel"]),
+    }
+}
+
+/// Returns a name for a room of given size, e.g. "the dusty study".
+pub fn room_name(width: usize, height: usize) -> String {
+    let grammar = base_grammar(width, height)
+        .rule("name", &[
+            "the {adj} {room}",
+            "the {room} of {of}",
+            "the {adj} {room} of {of}",
+            "{owner}'s {room}",
+            "{owner}'s {adj} {room}",
+        ]);
+
+    grammar.expand("{name}")
+}
+
+/// Returns what the player notices on entering a room of given kind and
+/// size, with given numbers of doors, npcs and items in it.
+pub fn room_description(
+    kind: &str,
+    width: usize,
+    height: usize,
+    doors: usize,
+    npcs: usize,
+    items: usize
+) -> String {
+    let grammar = base_grammar(width, height)
+        .rule("shape", match kind {
+            "square" => &["four straight walls", "walls that almost meet at right angles"][..],
+            _ => &["walls you can't quite count"][..],
+        })
+        .rule("size", match size_word(width, height) {
+            "small" => &["a cramped room", "a tight little room"][..],
+            "medium" => &["a room", "an ordinary room"][..],
+            _ => &["a wide room", "a room that goes on a little too long"][..],
+        })
+        .rule("doors", match doors {
+            0 => &["no doors at all"][..],
+            1 => &["a single door"][..],
+            2 => &["two doors"][..],
+            3..=4 => &["a few doors", "several doors"][..],
+            _ => &["more doors than it needs", "doors everywhere"][..],
+        })
+        .rule("npcs", match npcs {
+            0 => &[""][..],
+            1 => &[" Someone is here.", " You are not alone."][..],
+            _ => &[" Others are here.", " Several things are watching you."][..],
+        })
+        .rule("items", match items {
+            0 => &[""][..],
+            1 => &[" Something lies on the floor.", " Something glints on the floor."][..],
+            _ => &[" Things lie scattered about.", " The floor is cluttered."][..],
+        })
+        .rule("mood", &[
+            "It smells of {smell}.",
+            "You hear {sound}.",
+            "It smells of {smell}, and you hear {sound}.",
+        ])
+        .rule("description", &[
+            "{size} with {shape} and {doors}. {mood}{npcs}{items}",
+        ]);
+
+    capitalize(&grammar.expand("{description}"))
+}
@@ -1,11 +1,53 @@
 pub mod endless;
 
+use config::Config;
 use console::InputType;
 use tile::Tile;
 use ui::Ui;
 
+use std::fmt;
+use std::time::Duration;
+
+/// What went wrong making or running a game.
+#[derive(Debug)]
+pub enum GameError {
+    /// The world couldn't be made as asked, e.g. a room too small for its
+    /// doors.
+    Generation(String),
+
+    /// The world doesn't add up, e.g. a door leads to a room that isn't
+    /// there.
+    Corrupt(String),
+}
+
+impl fmt::Display for GameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &GameError::Generation(ref msg) => write!(f, "could not make the world: {}", msg),
+            &GameError::Corrupt(ref msg) => write!(f, "the world broke: {}", msg),
+        }
+    }
+}
+
 pub trait Game {
-    fn new(buf_width: usize, buf_height: usize) -> Self;
-    fn react(&mut self, input: InputType) -> Vec<Ui>;
-    fn gen_buffer(&mut self) -> Option<&Vec<Tile>>;
+    /// Returns a new game, generated as given config says.
+    fn new(config: &Config, buf_width: usize, buf_height: usize) -> Result<Self, GameError>
+        where Self: Sized;
+
+    /// Reacts to a single input.
+    fn react(&mut self, input: InputType) -> Result<Vec<Ui>, GameError>;
+
+    /// Advances anything that moves on its own, given the time since the last
+    /// tick. Called at a fixed rate whether or not there is input.
+    fn tick(&mut self, elapsed: Duration) -> Result<Vec<Ui>, GameError>;
+
+    /// Returns whether the game is reading typed text, so that keys should
+    /// be left to it rather than taken as commands.
+    fn wants_text(&self) -> bool {
+        false
+    }
+
+    /// Returns what the map shows right now, one tile per cell, or None if
+    /// there is nothing to show.
+    fn gen_buffer(&mut self) -> Result<Option<&Vec<Tile>>, GameError>;
 }
@@ -1,15 +1,18 @@
-#![feature(vec_resize_with)]
-
 extern crate pancurses;
 extern crate uuid;
 extern crate rand;
 extern crate num;
+extern crate libc;
 
 #[macro_use]
 extern crate bitflags;
 
+pub mod config;
 pub mod console;
+pub mod crash;
 pub mod game;
+pub mod scene;
+pub mod theme;
 pub mod tile;
 pub mod utils;
 pub mod ui;
@@ -19,10 +22,121 @@ use console::curses::CursesConsole;
 
 use game::Game;
 use game::endless::EndlessGame;
+use game::endless::export::Format;
+
+use scene::SceneStack;
+
+use theme::Theme;
+use config::Config;
+
+use std::env;
+use std::fs;
+use std::process;
 
 fn main() {
+    //  `--theme <name or path>` picks how tiles are drawn
+    let args: Vec<String> = env::args().collect();
+    let theme = match args.iter().position(|arg| arg == "--theme") {
+        None => Theme::classic(),
+        Some(i) => match args.get(i + 1).map(|arg| Theme::find(arg)) {
+            Some(Ok(theme)) => theme,
+            Some(Err(e)) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            },
+            None => {
+                eprintln!(
+                    "--theme needs a file or one of: {}",
+                    Theme::BUILTIN.join(", ")
+                );
+                process::exit(1);
+            },
+        },
+    };
+
+    //  world generation settings, see the config module
+    let config = match Config::from_args(&args) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        },
+    };
+
+    //  `--export <file> <name>` writes a new world to a .dot, .json or .html
+    //  file instead of playing, as the house of the player with that name
+    if let Some(i) = args.iter().position(|arg| arg == "--export") {
+        let path = match args.get(i + 1) {
+            Some(path) => path,
+            None => {
+                eprintln!("--export needs a file ending in .dot, .json or .html");
+                process::exit(1);
+            },
+        };
+
+        let format = match Format::from_path(path) {
+            Some(format) => format,
+            None => {
+                eprintln!("can't tell what to export {} as, use .dot, .json or .html", path);
+                process::exit(1);
+            },
+        };
+
+        let name = match args.get(i + 2) {
+            Some(name) if !name.starts_with("--") => name,
+            _ => {
+                eprintln!("--export needs the name of the player whose house it is after the file");
+                process::exit(1);
+            },
+        };
+
+        let game = match EndlessGame::new(&config, 80, 24) {
+            Ok(game) => game,
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            },
+        };
+
+        let text = match game.export(format, name) {
+            Ok(text) => text,
+            Err(e) => {
+                eprintln!("{}", e);
+                process::exit(1);
+            },
+        };
+
+        if let Err(e) = fs::write(path, text) {
+            eprintln!("could not write {}: {}", path, e);
+            process::exit(1);
+        }
+
+        return;
+    }
+
+    //  from here on the terminal is ours, so a panic has to give it back
+    crash::install_hook();
+
     let mut console = CursesConsole::new();
-    let game = EndlessGame::new(console.get_width(), console.get_height());
+    console.set_theme(theme);
+    console.set_glitch_level(config.glitch);
+    console.set_tick_rate(config.tick_rate);
+
+    //  returns once the player quits, or the game breaks; dropping the
+    //  console restores the terminal
+    let res = SceneStack::<EndlessGame>::new(
+        &config,
+        console.get_width(),
+        console.get_height()
+    ).and_then(|game| console.render(game));
+    drop(console);
 
-    console.render(game);
+    if let Err(e) = res {
+        eprintln!("{}", e);
+        match crash::write_report(&e.to_string()) {
+            Ok(path) => eprintln!("crash report written to {}", path.display()),
+            Err(e) => eprintln!("could not write a crash report: {}", e),
+        }
+        process::exit(1);
+    }
 }
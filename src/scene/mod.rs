@@ -0,0 +1,246 @@
+//  scenes sit on top of a Game: the title screen, the game itself, the pause
+//  menu and the end screen. Only the topmost scene gets input. The house has
+//  no end of its own, so the end screen only comes up when the player gives
+//  up from the pause menu
+
+use config::Config;
+use console::{ InputType, ArrowType, KeyType };
+use game::{ Game, GameError };
+use tile::Tile;
+use ui::*;
+
+use std::time::Duration;
+
+enum Scene {
+    Title(Menu),
+    Playing,
+    Paused(Menu),
+    Over(Menu),
+}
+
+/// What the selected menu item asks for.
+enum Choice {
+    NewGame,
+    Continue,
+    Resume,
+    GiveUp,
+    TitleScreen,
+    Quit,
+}
+
+impl Choice {
+    fn from_item(item: &str) -> Option<Self> {
+        match item {
+            "New game" => Some(Choice::NewGame),
+            "Continue" => Some(Choice::Continue),
+            "Resume" => Some(Choice::Resume),
+            "Give up" => Some(Choice::GiveUp),
+            "Title screen" => Some(Choice::TitleScreen),
+            "Quit" => Some(Choice::Quit),
+            _ => None,
+        }
+    }
+}
+
+pub struct SceneStack<G: Game> {
+    stack: Vec<Scene>,
+
+    //  the game in progress, kept around when going back to the title screen
+    //  so it can be continued
+    game: Option<G>,
+
+    //  what every new game gets made with
+    config: Config,
+
+    //  shown while no game is
+    buffer: Option<Vec<Tile>>,
+    buf_width: usize,
+    buf_height: usize,
+}
+
+impl<G: Game> SceneStack<G> {
+    fn title_menu(&self) -> Menu {
+        match self.game.is_some() {
+            true => Menu::new("o ä n d l i g", &["Continue", "New game", "Quit"]),
+            false => Menu::new("o ä n d l i g", &["New game", "Quit"]),
+        }
+    }
+
+    fn pause_menu() -> Menu {
+        Menu::new("Paused", &["Resume", "Give up", "Title screen", "Quit"])
+    }
+
+    fn over_menu() -> Menu {
+        Menu::new("The house goes on without you", &["Title screen", "Quit"])
+    }
+
+    //  the menu of the topmost scene, if it has one
+    fn top_menu(&mut self) -> Option<&mut Menu> {
+        match self.stack.last_mut() {
+            Some(&mut Scene::Title(ref mut menu)) => Some(menu),
+            Some(&mut Scene::Paused(ref mut menu)) => Some(menu),
+            Some(&mut Scene::Over(ref mut menu)) => Some(menu),
+            _ => None,
+        }
+    }
+
+    fn react_menu(&mut self, input: InputType) -> Result<Vec<Ui>, GameError> {
+        let mut res = Vec::new();
+
+        let choice = {
+            let menu = self.top_menu().unwrap();
+
+            match input {
+                InputType::Arrow(ArrowType::Up) => { menu.select_prev(); None },
+                InputType::Arrow(ArrowType::Down) => { menu.select_next(); None },
+                InputType::Key(KeyType::Enter) |
+                InputType::Char('\n') |
+                InputType::Char('\r') =>
+                    Choice::from_item(menu.get_selected()),
+                _ => None,
+            }
+        };
+
+        match choice {
+            None => (),
+
+            Some(Choice::NewGame) => {
+                //  a world that can't be made leaves us where we were
+                let mut game = match G::new(&self.config, self.buf_width, self.buf_height) {
+                    Ok(game) => game,
+                    Err(e) => {
+                        res.push(Ui::Message(MessageType::Static, MessagePosition::Bottom, e.to_string()));
+                        return Ok(res);
+                    },
+                };
+
+                self.stack.push(Scene::Playing);
+                res.push(Ui::Clear);
+                res.append(&mut game.react(InputType::FirstFrame)?);
+                self.game = Some(game);
+                return Ok(res);
+            },
+
+            Some(Choice::Continue) | Some(Choice::Resume) => {
+                match self.stack.last() {
+                    Some(&Scene::Paused(_)) => { self.stack.pop(); },
+                    _ => { self.stack.push(Scene::Playing); },
+                }
+                res.push(Ui::Menu(None));
+                return Ok(res);
+            },
+
+            Some(Choice::GiveUp) => {
+                self.stack.pop();
+                self.stack.pop();
+                self.game.take();
+                self.stack.push(Scene::Over(Self::over_menu()));
+                res.push(Ui::Clear);
+            },
+
+            Some(Choice::TitleScreen) => {
+                self.stack.truncate(1);
+                let menu = self.title_menu();
+                self.stack[0] = Scene::Title(menu);
+                res.push(Ui::Clear);
+            },
+
+            Some(Choice::Quit) => {
+                res.push(Ui::Quit);
+                return Ok(res);
+            },
+        }
+
+        res.push(Ui::Menu(self.top_menu().cloned()));
+        Ok(res)
+    }
+
+    fn gen_blank(&mut self) -> Option<&Vec<Tile>> {
+        let size = self.buf_width * self.buf_height;
+        if self.buffer.as_ref().map_or(true, |b| b.len() != size) {
+            self.buffer = Some(vec![Tile::void(); size]);
+        }
+
+        self.buffer.as_ref()
+    }
+}
+
+impl<G: Game> Game for SceneStack<G> {
+    fn new(config: &Config, buf_width: usize, buf_height: usize) -> Result<Self, GameError> {
+        let mut res = Self {
+            stack: Vec::new(),
+            game: None,
+            config: config.clone(),
+            buffer: None,
+            buf_width: buf_width,
+            buf_height: buf_height,
+        };
+
+        let menu = res.title_menu();
+        res.stack.push(Scene::Title(menu));
+        Ok(res)
+    }
+
+    fn react(&mut self, input: InputType) -> Result<Vec<Ui>, GameError> {
+        //  the game keeps track of the buffer size even while it's hidden
+        if let InputType::Resize(w, h) = input {
+            self.buf_width = w as usize;
+            self.buf_height = h as usize;
+            self.buffer.take();
+
+            if let Some(ref mut game) = self.game {
+                game.react(InputType::Resize(w, h))?;
+            }
+
+            return Ok(Vec::new());
+        }
+
+        match self.stack.last() {
+            Some(&Scene::Playing) => (),
+
+            _ => {
+                return match input {
+                    InputType::FirstFrame =>
+                        Ok(vec![Ui::Menu(self.top_menu().cloned())]),
+                    input => self.react_menu(input),
+                };
+            },
+        }
+
+        //  p is just a letter while the game reads text
+        let typing = self.game.as_ref().map_or(false, |game| game.wants_text());
+
+        match input {
+            InputType::Char(ch) if ch == '\u{1b}' || (ch == 'p' && !typing) => {
+                self.stack.push(Scene::Paused(Self::pause_menu()));
+                Ok(vec![Ui::Menu(self.top_menu().cloned())])
+            },
+
+            input => match self.game.as_mut() {
+                Some(game) => game.react(input),
+                None => Ok(Vec::new()),
+            },
+        }
+    }
+
+    fn tick(&mut self, elapsed: Duration) -> Result<Vec<Ui>, GameError> {
+        //  the world stands still while paused
+        match (self.stack.last(), self.game.as_mut()) {
+            (Some(&Scene::Playing), Some(game)) => game.tick(elapsed),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    fn gen_buffer(&mut self) -> Result<Option<&Vec<Tile>>, GameError> {
+        //  the pause menu is drawn over the game
+        let show_game = match self.stack.last() {
+            Some(&Scene::Playing) | Some(&Scene::Paused(_)) => true,
+            _ => false,
+        };
+
+        match (show_game, self.game.is_some()) {
+            (true, true) => self.game.as_mut().unwrap().gen_buffer(),
+            _ => Ok(self.gen_blank()),
+        }
+    }
+}
@@ -0,0 +1,386 @@
+//  themes decide what every base, cover and item looks like. A theme file is
+//  a list of `key = 'glyph' [attributes] [fg:color] [bg:color]` lines under a
+//  `[base]`, `[cover]` or `[item]` heading, e.g.
+//
+//      [base]
+//      wall    = '#'   bold    fg:dark7
+//      door    = '+'   fg:#c08040 bg:black
+//
+//      [item]
+//      brass-key = '-' bold    fg:yellow
+//
+//  items the theme leaves out keep their own glyph, in the `item` base style.
+//
+//  colors are `default`, the ANSI names (`red`, `bright-red`, ...), `dark1`
+//  through `dark8`, an xterm index 0 through 255, or `#rrggbb`
+
+use tile::base::Base;
+use tile::cover::Cover;
+use tile::style::{ Style, Attr, Color };
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::Path;
+
+/// A character and the style to draw it with.
+#[derive(Clone, Copy, Debug)]
+pub struct Glyph {
+    pub ch: char,
+    pub style: Style,
+}
+
+impl Glyph {
+    pub fn new(ch: char, style: Style) -> Self {
+        Self {
+            ch: ch,
+            style: style,
+        }
+    }
+}
+
+/// What went wrong loading a theme.
+#[derive(Debug)]
+pub enum ThemeError {
+    Io(io::Error),
+    Parse(usize, String),
+}
+
+impl fmt::Display for ThemeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            &ThemeError::Io(ref e) => write!(f, "could not read theme: {}", e),
+            &ThemeError::Parse(line, ref msg) =>
+                write!(f, "theme line {}: {}", line, msg),
+        }
+    }
+}
+
+impl From<io::Error> for ThemeError {
+    fn from(e: io::Error) -> Self {
+        ThemeError::Io(e)
+    }
+}
+
+enum Section {
+    Top,
+    Base,
+    Cover,
+    Item,
+}
+
+pub struct Theme {
+    name: String,
+    bases: HashMap<String, Glyph>,
+    covers: HashMap<String, Glyph>,
+    items: HashMap<String, Glyph>,
+}
+
+impl Theme {
+    /// Names of the themes that are built into the binary.
+    pub const BUILTIN: [&'static str; 3] = ["classic", "unicode", "high-contrast"];
+
+    /// Returns the built-in theme with given name.
+    pub fn builtin(name: &str) -> Option<Theme> {
+        let text = match name {
+            "classic" => include_str!("../../themes/classic.theme"),
+            "unicode" => include_str!("../../themes/unicode.theme"),
+            "high-contrast" => include_str!("../../themes/high-contrast.theme"),
+            _ => return None,
+        };
+
+        //  the built-in themes are checked in, so they had better parse
+        Some(Theme::parse(text).unwrap())
+    }
+
+    /// Returns the classic theme.
+    pub fn classic() -> Theme {
+        Theme::builtin("classic").unwrap()
+    }
+
+    /// Returns the built-in theme with given name, or else loads the theme
+    /// file at given path.
+    pub fn find(name_or_path: &str) -> Result<Theme, ThemeError> {
+        match Theme::builtin(name_or_path) {
+            Some(theme) => Ok(theme),
+            None => Theme::load(name_or_path),
+        }
+    }
+
+    /// Loads a theme file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Theme, ThemeError> {
+        let mut text = String::new();
+        File::open(path)?.read_to_string(&mut text)?;
+        Theme::parse(&text)
+    }
+
+    /// Parses the contents of a theme file.
+    pub fn parse(text: &str) -> Result<Theme, ThemeError> {
+        let mut res = Theme {
+            name: String::new(),
+            bases: HashMap::new(),
+            covers: HashMap::new(),
+            items: HashMap::new(),
+        };
+
+        let mut section = Section::Top;
+
+        for (i, line) in text.lines().enumerate() {
+            let line_no = i + 1;
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                section = match &line[1..line.len() - 1] {
+                    "base" => Section::Base,
+                    "cover" => Section::Cover,
+                    "item" => Section::Item,
+                    s => return Err(ThemeError::Parse(
+                        line_no, format!("unknown section `{}`", s)
+                    )),
+                };
+                continue;
+            }
+
+            let eq = match line.find('=') {
+                Some(eq) => eq,
+                None => return Err(ThemeError::Parse(
+                    line_no, String::from("expected `key = value`")
+                )),
+            };
+
+            let key = line[..eq].trim();
+            let value = line[eq + 1..].trim();
+
+            match section {
+                Section::Top => match key {
+                    "name" => { res.name = String::from(value); },
+                    _ => return Err(ThemeError::Parse(
+                        line_no, format!("unknown setting `{}`", key)
+                    )),
+                },
+
+                Section::Base => {
+                    let glyph = Self::parse_glyph(value)
+                        .map_err(|e| ThemeError::Parse(line_no, e))?;
+                    res.bases.insert(String::from(key), glyph);
+                },
+
+                Section::Cover => {
+                    let glyph = Self::parse_glyph(value)
+                        .map_err(|e| ThemeError::Parse(line_no, e))?;
+                    res.covers.insert(String::from(key), glyph);
+                },
+
+                Section::Item => {
+                    let glyph = Self::parse_glyph(value)
+                        .map_err(|e| ThemeError::Parse(line_no, e))?;
+                    res.items.insert(String::from(key), glyph);
+                },
+            }
+        }
+
+        Ok(res)
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns how given base looks. Bases the theme forgets about are drawn
+    /// as '?'.
+    pub fn get_base(&self, base: &Base) -> Glyph {
+        self.bases.get(base.key()).cloned()
+            .unwrap_or(Glyph::new('?', Style::default()))
+    }
+
+    /// Returns how a wall looks, given which of its neighbours are also
+    /// walls (or doors). Falls back to the plain `wall` glyph.
+    pub fn get_wall(&self, left: bool, right: bool, up: bool, down: bool) -> Glyph {
+        let key = match (left, right, up, down) {
+            (false, true, false, true) => "wall-ul",
+            (true, false, false, true) => "wall-ur",
+            (false, true, true, false) => "wall-ll",
+            (true, false, true, false) => "wall-lr",
+            (_, _, false, false) => "wall-h",
+            (false, false, _, _) => "wall-v",
+            _ => "wall",
+        };
+
+        self.bases.get(key).cloned()
+            .unwrap_or_else(|| self.get_base(&Base::Wall))
+    }
+
+    /// Returns how given cover looks, if the theme knows it.
+    pub fn get_cover(&self, cover: &Cover) -> Option<Glyph> {
+        self.covers.get(cover.key()).cloned()
+    }
+
+    /// Returns how the item of given kind looks, if the theme knows it.
+    pub fn get_item(&self, kind: &str) -> Option<Glyph> {
+        self.items.get(kind).cloned()
+    }
+
+    fn parse_glyph(value: &str) -> Result<Glyph, String> {
+        if !value.starts_with('\'') {
+            return Err(String::from("glyph must be quoted, e.g. 'x'"));
+        }
+
+        let rest = &value[1..];
+        let mut chars = rest.chars();
+        let ch = match (chars.next(), chars.next()) {
+            (Some(ch), Some('\'')) => ch,
+            _ => return Err(String::from("glyph must be a single character")),
+        };
+
+        let mut style = Style::default();
+        let words = &rest[ch.len_utf8() + 1..];
+
+        for word in words.split_whitespace() {
+            if word.starts_with("fg:") {
+                style.fg = Self::parse_color(&word[3..])?;
+                continue;
+            }
+
+            if word.starts_with("bg:") {
+                style.bg = Self::parse_color(&word[3..])?;
+                continue;
+            }
+
+            style.attr |= match word {
+                "standout" => Attr::STANDOUT,
+                "bold" => Attr::BOLD,
+                "underline" => Attr::UNDERLINE,
+                "dim" => Attr::DIM,
+                "reverse" => Attr::REVERSE,
+                "italic" => Attr::ITALIC,
+                _ => return Err(format!("unknown attribute `{}`", word)),
+            };
+        }
+
+        Ok(Glyph::new(ch, style))
+    }
+
+    fn parse_color(word: &str) -> Result<Color, String> {
+        const NAMES: [&'static str; 8] = [
+            "black", "red", "green", "yellow",
+            "blue", "magenta", "cyan", "white",
+        ];
+
+        if word == "default" {
+            return Ok(Color::Default);
+        }
+
+        if let Some(x) = NAMES.iter().position(|&n| n == word) {
+            return Ok(Color::Ansi(x as u8));
+        }
+
+        if word.starts_with("bright-") {
+            if let Some(x) = NAMES.iter().position(|&n| n == &word[7..]) {
+                return Ok(Color::Ansi(x as u8 + 8));
+            }
+        }
+
+        if word.starts_with("dark") {
+            match word[4..].parse::<u8>() {
+                Ok(x) if x >= 1 && x <= 8 => { return Ok(Color::dark(x)); },
+                _ => (),
+            }
+        }
+
+        if word.starts_with('#') && word.len() == 7 && word[1..].chars().all(|c| c.is_ascii_hexdigit()) {
+            let hex = |s: &str| u8::from_str_radix(s, 16);
+            match (hex(&word[1..3]), hex(&word[3..5]), hex(&word[5..7])) {
+                (Ok(r), Ok(g), Ok(b)) => { return Ok(Color::Rgb(r, g, b)); },
+                _ => (),
+            }
+        }
+
+        match word.parse::<u8>() {
+            Ok(x) => Ok(Color::Indexed(x)),
+            Err(_) => Err(format!("unknown color `{}`", word)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_err(text: &str) -> (usize, String) {
+        match Theme::parse(text) {
+            Err(ThemeError::Parse(line, msg)) => (line, msg),
+            Err(e) => panic!("expected a parse error, got {}", e),
+            Ok(_) => panic!("expected a parse error"),
+        }
+    }
+
+    #[test]
+    fn builtins_parse() {
+        for name in Theme::BUILTIN.iter() {
+            let theme = Theme::builtin(name).unwrap();
+            assert_eq!(theme.get_name(), *name);
+        }
+    }
+
+    #[test]
+    fn glyphs_and_styles() {
+        let theme = Theme::parse(concat!(
+            "# a comment\n",
+            "name = test\n",
+            "\n",
+            "[base]\n",
+            "wall = '#' bold underline fg:bright-red bg:dark3\n",
+            "door = '+' fg:#c08040 bg:17\n",
+            "\n",
+            "[cover]\n",
+            "spikes = '▲'\n",
+            "\n",
+            "[item]\n",
+            "brass-key = '-' fg:yellow\n",
+        )).unwrap();
+
+        let wall = theme.get_base(&Base::Wall);
+        assert_eq!(wall.ch, '#');
+        assert_eq!(wall.style, Style::new(Attr::BOLD | Attr::UNDERLINE, Color::Ansi(9), Color::dark(3)));
+
+        let door = theme.get_base(&Base::Door(0));
+        assert_eq!(door.ch, '+');
+        assert_eq!(door.style, Style::new(Attr::empty(), Color::Rgb(0xc0, 0x80, 0x40), Color::Indexed(17)));
+
+        assert_eq!(theme.get_cover(&Cover::Spikes).map(|glyph| glyph.ch), Some('▲'));
+        assert_eq!(theme.get_cover(&Cover::Locked).map(|glyph| glyph.ch), None);
+
+        let key = theme.get_item("brass-key").unwrap();
+        assert_eq!((key.ch, key.style.fg), ('-', Color::Ansi(3)));
+        assert!(theme.get_item("pebble").is_none());
+    }
+
+    #[test]
+    fn fallbacks() {
+        let theme = Theme::parse("[base]\nwall = 'X'\nwall-h = '-'\n").unwrap();
+
+        assert_eq!(theme.get_wall(true, true, false, false).ch, '-');
+        assert_eq!(theme.get_wall(false, false, true, true).ch, 'X');
+        assert_eq!(theme.get_base(&Base::Water).ch, '?');
+    }
+
+    #[test]
+    fn errors_say_where() {
+        assert_eq!(parse_err("[base]\nwall = X\n").0, 2);
+        assert_eq!(parse_err("[base]\n\nwall = 'XY'\n").0, 3);
+        assert_eq!(parse_err("[floor]\n").0, 1);
+        assert_eq!(parse_err("colour = red\n").0, 1);
+        assert_eq!(parse_err("[base]\nwall '#'\n").0, 2);
+
+        assert!(parse_err("[base]\nwall = '#' fg:mauve\n").1.contains("mauve"));
+        assert!(parse_err("[base]\nwall = '#' blink\n").1.contains("blink"));
+        assert!(parse_err("[base]\nwall = '#' fg:dark9\n").1.contains("dark9"));
+        assert!(parse_err("[base]\nwall = '#' fg:#12345g\n").1.contains("#12345g"));
+    }
+}
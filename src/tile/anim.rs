@@ -0,0 +1,110 @@
+//  tiles that move. An animation is a loop of keyframes, each shown for a few
+//  of the console's ticks, that change how a tile is drawn without the game
+//  having to send anything new. The console decides which keyframe shows, and
+//  the only randomness in that comes from the `fx_*` helpers, so animations
+//  never touch the game's numbers
+
+use tile::style::{ Style, Attr, Color };
+
+/// A change to how a tile's glyph is drawn.
+#[derive(PartialEq)]
+pub struct Keyframe {
+    /// Added to the glyph's attributes.
+    pub attr: Attr,
+
+    /// Replaces the glyph's foreground color, if any.
+    pub fg: Option<Color>,
+}
+
+impl Keyframe {
+    /// Returns given style with this keyframe applied.
+    pub fn apply(&self, style: Style) -> Style {
+        let style = style.with_attr(self.attr);
+        match self.fg {
+            Some(fg) => style.with_fg(fg),
+            None => style,
+        }
+    }
+}
+
+/// How the tiles sharing an animation keep time with each other.
+#[derive(PartialEq)]
+pub enum Phase {
+    /// All on the same keyframe.
+    Together,
+
+    /// Each a keyframe on from the one left of or above it, so the keyframes
+    /// roll across the room.
+    Wave,
+
+    /// Each on a keyframe of its own, picked at random every step.
+    Flicker,
+}
+
+#[derive(PartialEq)]
+pub struct Animation {
+    pub frames: &'static [Keyframe],
+
+    /// How many ticks each keyframe shows for.
+    pub ticks: usize,
+    pub phase: Phase,
+}
+
+impl Animation {
+    /// Returns the keyframe to show at given tick, for a tile at given
+    /// position in its room. Flickering uses `noise`, which should be random.
+    pub fn keyframe(&self, tick: usize, x: usize, y: usize, noise: usize) -> &Keyframe {
+        let step = tick / self.ticks;
+        let i = match self.phase {
+            Phase::Together => step,
+            Phase::Wave => step + x + y,
+            Phase::Flicker => scramble(step ^ noise, x, y),
+        };
+
+        &self.frames[i % self.frames.len()]
+    }
+}
+
+const PLAIN: Attr = Style::DEF.attr;
+
+/// Water, going lighter and darker in waves.
+pub static SHIMMER: Animation = Animation {
+    frames: &[
+        Keyframe { attr: PLAIN, fg: Some(Color::BLUE) },
+        Keyframe { attr: PLAIN, fg: Some(Color::CYAN) },
+        Keyframe { attr: Attr::BOLD, fg: Some(Color::CYAN) },
+        Keyframe { attr: PLAIN, fg: Some(Color::CYAN) },
+    ],
+    ticks: 6,
+    phase: Phase::Wave,
+};
+
+/// Lamps, mostly burning bright, sometimes not.
+pub static FLICKER: Animation = Animation {
+    frames: &[
+        Keyframe { attr: Attr::BOLD, fg: None },
+        Keyframe { attr: Attr::BOLD, fg: None },
+        Keyframe { attr: Attr::BOLD, fg: None },
+        Keyframe { attr: Attr::BOLD, fg: None },
+        Keyframe { attr: PLAIN, fg: None },
+        Keyframe { attr: Attr::DIM, fg: None },
+    ],
+    ticks: 4,
+    phase: Phase::Flicker,
+};
+
+/// Doors, slowly brightening and fading all together.
+pub static PULSE: Animation = Animation {
+    frames: &[
+        Keyframe { attr: PLAIN, fg: None },
+        Keyframe { attr: Attr::BOLD, fg: None },
+    ],
+    ticks: 20,
+    phase: Phase::Together,
+};
+
+//  mixes given numbers up well enough that neighbouring tiles look unrelated
+fn scramble(n: usize, x: usize, y: usize) -> usize {
+    let h = (n ^ x.wrapping_mul(0x9e37) ^ y.wrapping_mul(0x85eb)).wrapping_mul(0x2545_f491);
+    h ^ (h >> 15)
+}
@@ -2,12 +2,34 @@
 pub enum Base {
     Void,
     Player,
+    Npc(char),
+    //  an item's own glyph, and its kind for themes to look up
+    Item(char, &'static str),
 
     Ground,
     Wall,
     Water,
     Message(String),
     Door(usize),
+    Lamp,
+}
+
+impl Base {
+    /// Returns the name themes use for this base.
+    pub fn key(&self) -> &'static str {
+        match self {
+            &Base::Void => "void",
+            &Base::Player => "player",
+            &Base::Npc(_) => "npc",
+            &Base::Item(_, _) => "item",
+            &Base::Ground => "ground",
+            &Base::Wall => "wall",
+            &Base::Water => "water",
+            &Base::Message(_) => "message",
+            &Base::Door(_) => "door",
+            &Base::Lamp => "lamp",
+        }
+    }
 }
 
 impl Default for Base {
@@ -15,4 +37,3 @@ impl Default for Base {
         Base::Ground
     }
 }
-
@@ -1,4 +1,29 @@
-#[derive(Clone)]
+#[derive(PartialEq, Eq, Clone)]
 pub enum Cover {
     Spikes,
+
+    /// Marks a door that lets you through but not back.
+    OneWay,
+
+    /// Marks a door that won't open from this side.
+    Shut,
+
+    /// Marks a door that leads back somewhere else.
+    Twisted,
+
+    /// Marks a door that needs a key.
+    Locked,
+}
+
+impl Cover {
+    /// Returns the name themes use for this cover.
+    pub fn key(&self) -> &'static str {
+        match self {
+            &Cover::Spikes => "spikes",
+            &Cover::OneWay => "one-way",
+            &Cover::Shut => "shut",
+            &Cover::Twisted => "twisted",
+            &Cover::Locked => "locked",
+        }
+    }
 }
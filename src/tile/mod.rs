@@ -1,18 +1,35 @@
+pub mod anim;
 pub mod base;
 pub mod cover;
 pub mod style;
 
+use self::anim::{ Animation, SHIMMER, FLICKER, PULSE };
 use self::base::Base;
 use self::cover::Cover;
-use self::style::Style;
+use self::style::{ Style, FULL_LIGHT };
 
 use std::mem;
 
-#[derive(Clone)]
+#[derive(PartialEq, Clone)]
 pub struct Tile {
     pub base: Base,
     pub cover: Option<Cover>,
-    pub style: Style,
+
+    //  None means the theme decides
+    pub style: Option<Style>,
+
+    //  how it moves, if it does, and where it is in its room, so that
+    //  animations stay put when the view moves
+    pub anim: Option<&'static Animation>,
+    pub place: (usize, usize),
+
+    //  out of sight, and drawn the way the player remembers it, or else how
+    //  much light falls on it, up to `FULL_LIGHT`
+    pub remembered: bool,
+    pub light: u8,
+
+    //  picked out, e.g. as where to travel to
+    pub highlight: bool,
 }
 
 impl Tile {
@@ -20,7 +37,12 @@ impl Tile {
         Tile {
             base: base,
             cover: cover,
-            style: style,
+            style: Some(style),
+            anim: None,
+            place: (0, 0),
+            remembered: false,
+            light: FULL_LIGHT,
+            highlight: false,
         }
     }
 
@@ -28,7 +50,12 @@ impl Tile {
         Tile {
             base: Base::Ground,
             cover: Option::default(),
-            style: Style::DEF_DIM,
+            style: None,
+            anim: None,
+            place: (0, 0),
+            remembered: false,
+            light: FULL_LIGHT,
+            highlight: false,
         }
     }
 
@@ -36,7 +63,12 @@ impl Tile {
         Tile {
             base: Base::Wall,
             cover: Option::default(),
-            style: Style::default(),
+            style: None,
+            anim: None,
+            place: (0, 0),
+            remembered: false,
+            light: FULL_LIGHT,
+            highlight: false,
         }
     }
 
@@ -44,7 +76,38 @@ impl Tile {
         Tile {
             base: Base::Door(index),
             cover: Option::default(),
-            style: Style::DEF_BOLD,
+            style: None,
+            anim: Some(&PULSE),
+            place: (0, 0),
+            remembered: false,
+            light: FULL_LIGHT,
+            highlight: false,
+        }
+    }
+
+    pub fn lamp() -> Tile {
+        Tile {
+            base: Base::Lamp,
+            cover: Option::default(),
+            style: None,
+            anim: Some(&FLICKER),
+            place: (0, 0),
+            remembered: false,
+            light: FULL_LIGHT,
+            highlight: false,
+        }
+    }
+
+    pub fn water() -> Tile {
+        Tile {
+            base: Base::Water,
+            cover: Option::default(),
+            style: None,
+            anim: Some(&SHIMMER),
+            place: (0, 0),
+            remembered: false,
+            light: FULL_LIGHT,
+            highlight: false,
         }
     }
 
@@ -52,7 +115,12 @@ impl Tile {
         Tile {
             base: Base::Void,
             cover: Option::default(),
-            style: Style::default(),
+            style: None,
+            anim: None,
+            place: (0, 0),
+            remembered: false,
+            light: FULL_LIGHT,
+            highlight: false,
         }
     }
 
@@ -60,10 +128,55 @@ impl Tile {
         Tile {
             base: Base::Player,
             cover: Option::default(),
-            style: Style::default(),
+            style: None,
+            anim: None,
+            place: (0, 0),
+            remembered: false,
+            light: FULL_LIGHT,
+            highlight: false,
         }
     }
 
+    pub fn npc(glyph: char, style: Option<Style>) -> Tile {
+        Tile {
+            base: Base::Npc(glyph),
+            cover: Option::default(),
+            style: style,
+            anim: None,
+            place: (0, 0),
+            remembered: false,
+            light: FULL_LIGHT,
+            highlight: false,
+        }
+    }
+
+    pub fn item(glyph: char, kind: &'static str) -> Tile {
+        Tile {
+            base: Base::Item(glyph, kind),
+            cover: Option::default(),
+            style: None,
+            anim: None,
+            place: (0, 0),
+            remembered: false,
+            light: FULL_LIGHT,
+            highlight: false,
+        }
+    }
+
+    /// Returns whether something can stand on this tile.
+    pub fn is_passable(&self) -> bool {
+        match self.base {
+            Base::Ground => true,
+            _ => false,
+        }
+    }
+
+    /// Returns this tile placed at given position in its room.
+    pub fn at(mut self, x: usize, y: usize) -> Tile {
+        self.place = (x, y);
+        self
+    }
+
     pub fn has_base(&self, base: &Base) -> bool {
         mem::discriminant(&self.base) == mem::discriminant(base)
     }
@@ -74,7 +187,12 @@ impl Default for Tile {
         Tile {
             base: Base::default(),
             cover: Option::default(),
-            style: Style::default(),
+            style: None,
+            anim: None,
+            place: (0, 0),
+            remembered: false,
+            light: FULL_LIGHT,
+            highlight: false,
         }
     }
 }
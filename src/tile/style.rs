@@ -1,36 +1,199 @@
+//  this module does not know about ncurses either; the console decides how a
+//  `Color` is downgraded to whatever the terminal supports
+
 use utils::rnd_within;
 
+use std::cmp;
+
+/// The brightest a tile gets; in less light, its colors are darkened.
+pub const FULL_LIGHT: u8 = 8;
+
 bitflags! {
-    pub struct Style: u32 {
+    pub struct Attr: u32 {
         const STANDOUT      = 1 << 0;
         const BOLD          = 1 << 1;
         const UNDERLINE     = 1 << 2;
         const DIM           = 1 << 3;
-        
-        const DARK1        = 1 << 4;
-        const DARK2        = 1 << 5;
-        const DARK3        = 1 << 6;
-        const DARK4        = 1 << 7;
-        const DARK5        = 1 << 8;
-        const DARK6        = 1 << 9;
-        const DARK7        = 1 << 10;
-        const DARK8        = 1 << 11;
-       
-        const BLACK         = 1 << 12;
-        const RED           = 1 << 13;
-        const GREEN         = 1 << 14;
-        const YELLOW        = 1 << 15;
-        const BLUE          = 1 << 16;
-        const MAGENTA       = 1 << 17;
-        const CYAN          = 1 << 18;
-        const WHITE         = 1 << 19;
-
-        const DEF           = Self::BLACK.bits;
-        const DEF_STANDOUT  = Self::BLACK.bits | Self::STANDOUT.bits;
-        const DEF_BOLD      = Self::BLACK.bits | Self::BOLD.bits;
-        const DEF_UNDERLINE = Self::BLACK.bits | Self::UNDERLINE.bits;
-        const DEF_DIM       = Self::BLACK.bits | Self::DIM.bits;
+        const REVERSE       = 1 << 4;
+        const ITALIC        = 1 << 5;
+    }
+}
+
+impl Default for Attr {
+    fn default() -> Self {
+        Attr::empty()
+    }
+}
+
+/// A terminal color, at one of three depths.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub enum Color {
+    /// Whatever the terminal uses when nothing is set.
+    Default,
+
+    /// One of the 16 ANSI colors, 0 through 15.
+    Ansi(u8),
+
+    /// One of the 256 xterm colors.
+    Indexed(u8),
+
+    /// A 24-bit color.
+    Rgb(u8, u8, u8),
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Color::Default
+    }
+}
+
+//  the xterm 6x6x6 color cube uses these intensities
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+//  approximate rgb values of the 16 ANSI colors, as xterm draws them
+const ANSI_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),          (205, 0, 0),        (0, 205, 0),        (205, 205, 0),
+    (0, 0, 238),        (205, 0, 205),      (0, 205, 205),      (229, 229, 229),
+    (127, 127, 127),    (255, 0, 0),        (0, 255, 0),        (255, 255, 0),
+    (92, 92, 255),      (255, 0, 255),      (0, 255, 255),      (255, 255, 255),
+];
+
+impl Color {
+    pub const BLACK: Color = Color::Ansi(0);
+    pub const RED: Color = Color::Ansi(1);
+    pub const GREEN: Color = Color::Ansi(2);
+    pub const YELLOW: Color = Color::Ansi(3);
+    pub const BLUE: Color = Color::Ansi(4);
+    pub const MAGENTA: Color = Color::Ansi(5);
+    pub const CYAN: Color = Color::Ansi(6);
+    pub const WHITE: Color = Color::Ansi(7);
+
+    //  grey ramp, darkest first
+    pub const DARK1: Color = Color::Rgb(28, 28, 28);
+    pub const DARK2: Color = Color::Rgb(57, 57, 57);
+    pub const DARK3: Color = Color::Rgb(85, 85, 85);
+    pub const DARK4: Color = Color::Rgb(113, 113, 113);
+    pub const DARK5: Color = Color::Rgb(142, 142, 142);
+    pub const DARK6: Color = Color::Rgb(170, 170, 170);
+    pub const DARK7: Color = Color::Rgb(198, 198, 198);
+    pub const DARK8: Color = Color::Rgb(227, 227, 227);
+
+    /// Returns the grey at given level of the dark ramp, clamped to 1..=8.
+    pub fn dark(level: u8) -> Self {
+        match level {
+            0..=1 => Color::DARK1,
+            2 => Color::DARK2,
+            3 => Color::DARK3,
+            4 => Color::DARK4,
+            5 => Color::DARK5,
+            6 => Color::DARK6,
+            7 => Color::DARK7,
+            _ => Color::DARK8,
+        }
+    }
+
+    /// Returns a random ANSI or dark color.
+    pub fn rnd() -> Self {
+        match rnd_within::<u8>(0..24) {
+            x @ 0..=15 => Color::Ansi(x),
+            x => Color::dark(x - 15),
+        }
+    }
+
+    /// Returns the rgb value of this color, or None for the default color.
+    pub fn to_rgb(&self) -> Option<(u8, u8, u8)> {
+        match *self {
+            Color::Default => None,
+            Color::Ansi(x) => Some(ANSI_RGB[(x & 15) as usize]),
+            Color::Indexed(x) if x < 16 => Some(ANSI_RGB[x as usize]),
+            Color::Indexed(x) if x < 232 => {
+                let x = x - 16;
+                Some((
+                    CUBE_LEVELS[(x / 36) as usize],
+                    CUBE_LEVELS[(x / 6 % 6) as usize],
+                    CUBE_LEVELS[(x % 6) as usize],
+                ))
+            },
+            Color::Indexed(x) => {
+                let l = 8 + (x - 232) * 10;
+                Some((l, l, l))
+            },
+            Color::Rgb(r, g, b) => Some((r, g, b)),
+        }
+    }
+
+    /// Downgrades this color to one of the 256 xterm colors.
+    pub fn to_indexed(&self) -> Color {
+        match *self {
+            Color::Default => Color::Default,
+            Color::Ansi(x) => Color::Indexed(x & 15),
+            Color::Indexed(x) => Color::Indexed(x),
+            Color::Rgb(r, g, b) => {
+                //  pick whichever of the cube and the grey ramp is closer
+                let cube = |v: u8| -> u8 {
+                    match v {
+                        0..=47 => 0,
+                        48..=114 => 1,
+                        v => (v - 35) / 40,
+                    }
+                };
+                let (cr, cg, cb) = (cube(r), cube(g), cube(b));
+                let cube_idx = 16 + 36 * cr + 6 * cg + cb;
+
+                let avg = ((r as u32 + g as u32 + b as u32) / 3) as u8;
+                let grey_idx = match avg {
+                    0..=7 => 232,
+                    248..=255 => 255,
+                    v => 232 + (v - 8) / 10,
+                };
+
+                let dc = dist(
+                    (r, g, b),
+                    Color::Indexed(cube_idx).to_rgb().unwrap()
+                );
+                let dg = dist(
+                    (r, g, b),
+                    Color::Indexed(grey_idx).to_rgb().unwrap()
+                );
+
+                match dg < dc {
+                    true => Color::Indexed(grey_idx),
+                    false => Color::Indexed(cube_idx),
+                }
+            },
+        }
     }
+
+    /// Downgrades this color to one of the 16 ANSI colors.
+    pub fn to_ansi(&self) -> Color {
+        match *self {
+            Color::Default => Color::Default,
+            Color::Ansi(x) => Color::Ansi(x & 15),
+            Color::Indexed(x) if x < 16 => Color::Ansi(x),
+            _ => {
+                let rgb = self.to_rgb().unwrap();
+                let best = (0..16u8)
+                    .min_by_key(|&x| dist(rgb, ANSI_RGB[x as usize]))
+                    .unwrap();
+                Color::Ansi(best)
+            },
+        }
+    }
+}
+
+fn dist(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let dr = a.0 as i32 - b.0 as i32;
+    let dg = a.1 as i32 - b.1 as i32;
+    let db = a.2 as i32 - b.2 as i32;
+    (dr * dr + dg * dg + db * db) as u32
+}
+
+/// How a tile looks: attributes plus a foreground and a background color.
+#[derive(PartialEq, Eq, Hash, Clone, Copy, Debug)]
+pub struct Style {
+    pub attr: Attr,
+    pub fg: Color,
+    pub bg: Color,
 }
 
 impl Default for Style {
@@ -40,26 +203,129 @@ impl Default for Style {
 }
 
 impl Style {
+    pub const DEF: Style = Style {
+        attr: Attr { bits: 0 },
+        fg: Color::Default,
+        bg: Color::Default,
+    };
+
+    pub const DEF_STANDOUT: Style = Style {
+        attr: Attr::STANDOUT,
+        fg: Color::Default,
+        bg: Color::Default,
+    };
+
+    pub const DEF_BOLD: Style = Style {
+        attr: Attr::BOLD,
+        fg: Color::Default,
+        bg: Color::Default,
+    };
+
+    pub const DEF_UNDERLINE: Style = Style {
+        attr: Attr::UNDERLINE,
+        fg: Color::Default,
+        bg: Color::Default,
+    };
+
+    pub const DEF_DIM: Style = Style {
+        attr: Attr::DIM,
+        fg: Color::Default,
+        bg: Color::Default,
+    };
+
+    pub fn new(attr: Attr, fg: Color, bg: Color) -> Self {
+        Self {
+            attr: attr,
+            fg: fg,
+            bg: bg,
+        }
+    }
+
+    pub fn with_attr(mut self, attr: Attr) -> Self {
+        self.attr |= attr;
+        self
+    }
+
+    pub fn with_fg(mut self, fg: Color) -> Self {
+        self.fg = fg;
+        self
+    }
+
+    pub fn with_bg(mut self, bg: Color) -> Self {
+        self.bg = bg;
+        self
+    }
+
+    /// Returns this style as it looks in given light, out of `FULL_LIGHT`.
+    /// The default foreground darkens as if it were light grey, and the
+    /// default background stays as the terminal has it.
+    pub fn in_light(mut self, light: u8) -> Self {
+        let light = cmp::min(light, FULL_LIGHT) as u32;
+        let darken = |color: Color| {
+            let (r, g, b) = color.to_rgb().unwrap_or(ANSI_RGB[7]);
+            let scale = |v: u8| (v as u32 * light / FULL_LIGHT as u32) as u8;
+            Color::Rgb(scale(r), scale(g), scale(b))
+        };
+
+        self.fg = darken(self.fg);
+        if self.bg != Color::Default {
+            self.bg = darken(self.bg);
+        }
+        self
+    }
+
+    /// Returns a style with random foreground color and default everything
+    /// else.
     pub fn rnd_color() -> Self {
-        //  1 << rnd_within::<u32>(4..20))
-        match rnd_within::<u8>(4..20) {
-            4 => Style::DARK1,
-            5 => Style::DARK2,
-            6 => Style::DARK3,
-            7 => Style::DARK4,
-            8 => Style::DARK5,
-            9 => Style::DARK6,
-            10 => Style::DARK7,
-            11 => Style::DARK8,
-            12 => Style::BLACK,
-            13 => Style::RED,
-            14 => Style::GREEN,
-            15 => Style::YELLOW,
-            16 => Style::BLUE,
-            17 => Style::MAGENTA,
-            18 => Style::CYAN,
-            19 => Style::WHITE,
-            _ => panic!(),
+        Style::DEF.with_fg(Color::rnd())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn indexed_colors_come_back() {
+        for x in 16..=255u8 {
+            let rgb = Color::Indexed(x).to_rgb().unwrap();
+            let back = Color::Rgb(rgb.0, rgb.1, rgb.2).to_indexed();
+            assert_eq!(back.to_rgb(), Some(rgb), "xterm color {}", x);
         }
     }
+
+    #[test]
+    fn nearest_indexed() {
+        assert_eq!(Color::Rgb(250, 2, 3).to_indexed(), Color::Indexed(196));
+        assert_eq!(Color::Rgb(100, 100, 100).to_indexed(), Color::Indexed(241));
+        assert_eq!(Color::Rgb(0, 0, 0).to_indexed(), Color::Indexed(16));
+        assert_eq!(Color::Ansi(3).to_indexed(), Color::Indexed(3));
+        assert_eq!(Color::Default.to_indexed(), Color::Default);
+    }
+
+    #[test]
+    fn nearest_ansi() {
+        assert_eq!(Color::Rgb(255, 10, 0).to_ansi(), Color::Ansi(9));
+        assert_eq!(Color::Rgb(190, 0, 0).to_ansi(), Color::Ansi(1));
+        assert_eq!(Color::Rgb(5, 5, 5).to_ansi(), Color::Ansi(0));
+        assert_eq!(Color::Indexed(5).to_ansi(), Color::Ansi(5));
+        assert_eq!(Color::Indexed(231).to_ansi(), Color::Ansi(15));
+        assert_eq!(Color::DARK4.to_ansi(), Color::Ansi(8));
+        assert_eq!(Color::Default.to_ansi(), Color::Default);
+    }
+
+    #[test]
+    fn darker_in_less_light() {
+        let style = Style::DEF_BOLD.with_fg(Color::Rgb(200, 100, 0));
+        assert_eq!(style.in_light(FULL_LIGHT), style.with_fg(Color::Rgb(200, 100, 0)));
+        assert_eq!(style.in_light(FULL_LIGHT / 2).fg, Color::Rgb(100, 50, 0));
+        assert_eq!(style.in_light(0).fg, Color::Rgb(0, 0, 0));
+        assert_eq!(style.in_light(0).attr, Attr::BOLD);
+
+        //  the default background is left to the terminal
+        let style = Style::DEF.in_light(FULL_LIGHT / 2);
+        assert_eq!(style.fg, Color::Rgb(114, 114, 114));
+        assert_eq!(style.bg, Color::Default);
+        assert_eq!(Style::DEF.with_bg(Color::WHITE).in_light(0).bg, Color::Rgb(0, 0, 0));
+    }
 }
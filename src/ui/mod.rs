@@ -7,6 +7,68 @@ pub enum MessagePosition {
     Bottom,
 }
 
+/// A titled list of choices, one of which is selected.
+#[derive(Clone)]
+pub struct Menu {
+    pub title: String,
+    pub items: Vec<String>,
+    pub selected: usize,
+}
+
+impl Menu {
+    pub fn new(title: &str, items: &[&str]) -> Self {
+        Self {
+            title: String::from(title),
+            items: items.iter().map(|s| String::from(*s)).collect(),
+            selected: 0,
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.items.len() {
+            self.selected += 1;
+        }
+    }
+
+    pub fn get_selected(&self) -> &str {
+        &self.items[self.selected]
+    }
+}
+
+/// A full-screen list, e.g. the inventory.
+#[derive(Clone)]
+pub struct Panel {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub selected: Option<usize>,
+
+    //  usually a reminder of which keys do what
+    pub footer: String,
+}
+
 pub enum Ui {
     Message(MessageType, MessagePosition, String),
+
+    //  shows a menu over everything else, or hides it if None
+    Menu(Option<Menu>),
+
+    //  shows a panel over the map, or hides it if None
+    Panel(Option<Panel>),
+
+    //  replaces the status bar, and the lines of the side panel
+    Status(String),
+    Side(Vec<String>),
+
+    //  hides the message, the menu, the panel, the status bar and the side
+    //  panel
+    Clear,
+
+    //  the player is done, so the console should stop
+    Quit,
 }
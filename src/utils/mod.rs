@@ -2,16 +2,41 @@
 //  not `pancurses::chtype`
 
 use rand::*;
+use rand::rngs::StdRng;
+use std::cell::RefCell;
 use std::ops::Range;
 use std::iter;
 use std::cmp;
 
+thread_local! {
+    //  everything random in the game comes from here, so that a seed always
+    //  gives the same game
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_rng(thread_rng()).unwrap());
+}
+
+/// Restarts the game's random numbers from given seed.
+pub fn seed(seed: u64) {
+    let mut bytes = [0; 32];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = (seed >> (i % 8 * 8)) as u8;
+    }
+
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::from_seed(bytes));
+}
+
+/// Returns a random value.
+pub fn rnd<T>() -> T
+where distributions::Standard: distributions::Distribution<T>
+{
+    RNG.with(|rng| rng.borrow_mut().gen())
+}
+
 /// Returns a random number within given range.
 pub fn rnd_within<Idx>(range: Range<Idx>) -> Idx
 where distributions::Standard: distributions::Distribution<Idx>,
       Idx: ::num::Integer + Copy
 {
-    random::<Idx>() % (range.end - range.start) + range.start
+    rnd::<Idx>() % (range.end - range.start) + range.start
 }
 
 /// Returns a random number less than given number.
@@ -19,19 +44,31 @@ pub fn rnd_lt<Num>(x: Num) -> Num
 where distributions::Standard: distributions::Distribution<Num>,
       Num: ::num::Integer + Copy
 {
-    random::<Num>() % x
+    rnd::<Num>() % x
 }
 
 /// Returns a random character.
 pub fn rnd_ch() -> char {
-    repeat_until(random::<char>, char::is_ascii_graphic)
+    repeat_until(rnd::<char>, char::is_ascii_graphic)
 }
 
 /// Returns a string with random characters at given length.
 pub fn rnd_string(len: usize) -> String {
-    iter::repeat_with(
-        || repeat_until(random::<char>, char::is_ascii_graphic)
-    ).take(len).collect::<String>()
+    iter::repeat_with(rnd_ch).take(len).collect::<String>()
+}
+
+/// Like `rnd_lt`, but never seeded and apart from the game's numbers, for
+/// effects that must not change what happens in the game.
+pub fn fx_lt<Num>(x: Num) -> Num
+where distributions::Standard: distributions::Distribution<Num>,
+      Num: ::num::Integer + Copy
+{
+    random::<Num>() % x
+}
+
+/// Like `rnd_ch`, but see `fx_lt`.
+pub fn fx_ch() -> char {
+    repeat_until(random::<char>, char::is_ascii_graphic)
 }
 
 /// Repeat given closure until the return satisfies given predicate.